@@ -0,0 +1,47 @@
+//! Save the sound of a forecast as a WAV file, like the "Save as WAV" button.
+//!
+//! The forecast is a JSON response of open-meteo.com with the hourly
+//! `temperature_2m` and `precipitation`, for example:
+//!
+//! ```sh
+//! curl -o forecast.json "https://api.open-meteo.com/v1/forecast?latitude=48.3&longitude=16.3&hourly=temperature_2m,precipitation"
+//! cargo run --example sonify -- forecast.json forecast.wav
+//! ```
+
+use anyhow::{bail, Context};
+use serde_json::Value;
+use weather::sonify::{render, wav};
+
+/// Hourly values of a series, missing ones being not a number.
+fn hourly(forecast: &Value, name: &str) -> anyhow::Result<Vec<f64>> {
+    let values = forecast["hourly"][name]
+        .as_array()
+        .with_context(|| format!("no hourly {name} in the forecast"))?;
+    Ok(values
+        .iter()
+        .map(|value| value.as_f64().unwrap_or(f64::NAN))
+        .collect())
+}
+
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [input, output] = args.as_slice() else {
+        bail!("usage: sonify <forecast.json> <output.wav>");
+    };
+    let forecast: Value = serde_json::from_str(
+        &std::fs::read_to_string(input).with_context(|| format!("failed to read {input}"))?,
+    )
+    .with_context(|| format!("failed to parse {input}"))?;
+
+    let temperatures = hourly(&forecast, "temperature_2m")?;
+    let mut precipitation = hourly(&forecast, "precipitation")?;
+    if forecast["hourly_units"]["precipitation"] == "inch" {
+        for amount in &mut precipitation {
+            *amount *= 25.4;
+        }
+    }
+    std::fs::write(output, wav(&render(&temperatures, &precipitation)))
+        .with_context(|| format!("failed to write {output}"))?;
+    println!("saved {} hours to {output}", temperatures.len());
+    Ok(())
+}
@@ -0,0 +1,151 @@
+//! Forecasts already downloaded, kept in memory and in the browser's local storage.
+//!
+//! Entries are keyed by the API query, with coordinates rounded, so that changing
+//! the requested variables (the request schema) never serves outdated entries. As
+//! entries are stored in the units shown, the key also tells those: the same query
+//! is shown in m/s or on the Beaufort scale.
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{data::Data, storage, units::Units};
+
+const STORAGE_KEY: &str = "weather.forecasts";
+/// Forecast models are updated about every hour: after that, data should be revalidated.
+const TIME_TO_LIVE_MS: f64 = 60.0 * 60.0 * 1000.0;
+/// Older entries are discarded, even when offline.
+const MAX_AGE_MS: f64 = 48.0 * 60.0 * 60.0 * 1000.0;
+const MAX_ENTRIES: usize = 10;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Entry {
+    key: String,
+    /// Milliseconds since the Unix epoch.
+    pub fetched_at: f64,
+    pub data: Data,
+}
+
+impl Entry {
+    /// Whether the forecast model could have been updated since the entry was fetched.
+    pub fn is_fresh(&self, now: f64) -> bool {
+        now - self.fetched_at < TIME_TO_LIVE_MS
+    }
+}
+
+/// Cache key for a query: coordinates are rounded to about one kilometre.
+pub fn key(latitude: f64, longitude: f64, forecast_days: usize, units: Units) -> String {
+    let round = |x: f64| (x * 100.0).round() / 100.0;
+    format!(
+        "{}#wind_speed={}",
+        Data::api_query(round(latitude), round(longitude), forecast_days, units),
+        units.wind_speed.symbol()
+    )
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Cache {
+    entries: Rc<RefCell<Vec<Entry>>>,
+}
+
+impl Cache {
+    /// Cache holding the entries persisted in the browser storage.
+    pub fn load() -> Self {
+        let entries = storage::get(STORAGE_KEY).unwrap_or_default();
+        Cache {
+            entries: Rc::new(RefCell::new(entries)),
+        }
+    }
+
+    pub fn get(&self, key: &str, now: f64) -> Option<Entry> {
+        self.entries
+            .borrow()
+            .iter()
+            .find(|entry| entry.key == key && now - entry.fetched_at < MAX_AGE_MS)
+            .cloned()
+    }
+
+    /// Add an entry in memory, dropping expired and oldest ones.
+    fn insert(&self, key: String, data: Data, now: f64) {
+        let mut entries = self.entries.borrow_mut();
+        entries.retain(|entry| entry.key != key && now - entry.fetched_at < MAX_AGE_MS);
+        entries.insert(
+            0,
+            Entry {
+                key,
+                fetched_at: now,
+                data,
+            },
+        );
+        entries.truncate(MAX_ENTRIES);
+    }
+
+    /// Add an entry and persist the cache in the browser storage.
+    pub fn store(&self, key: String, data: Data, now: f64) {
+        self.insert(key, data, now);
+        storage::set(STORAGE_KEY, &*self.entries.borrow());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::WindSpeedUnit;
+
+    const HOUR_MS: f64 = 60.0 * 60.0 * 1000.0;
+
+    fn vienna(forecast_days: usize) -> String {
+        key(48.3, 16.3, forecast_days, Units::METRIC)
+    }
+
+    #[test]
+    fn nearby_coordinates_share_entries() {
+        assert_eq!(key(48.301, 16.299, 2, Units::METRIC), vienna(2));
+        assert_ne!(vienna(3), vienna(2));
+        assert_ne!(key(48.3, 16.3, 2, Units::IMPERIAL), vienna(2));
+    }
+
+    #[test]
+    fn units_shown_have_their_own_entries() {
+        let metres_per_second = Units {
+            wind_speed: WindSpeedUnit::MetresPerSecond,
+            ..Units::METRIC
+        };
+        let beaufort = Units {
+            wind_speed: WindSpeedUnit::Beaufort,
+            ..Units::METRIC
+        };
+        // Both are requested in m/s
+        assert_eq!(
+            Data::api_query(48.3, 16.3, 2, metres_per_second),
+            Data::api_query(48.3, 16.3, 2, beaufort)
+        );
+        assert_ne!(
+            key(48.3, 16.3, 2, metres_per_second),
+            key(48.3, 16.3, 2, beaufort)
+        );
+        let cache = Cache::default();
+        cache.insert(key(48.3, 16.3, 2, beaufort), Data::default(), 0.0);
+        assert!(cache
+            .get(&key(48.3, 16.3, 2, metres_per_second), 0.0)
+            .is_none());
+    }
+
+    #[test]
+    fn entries_expire() {
+        let cache = Cache::default();
+        cache.insert(vienna(2), Data::default(), 0.0);
+        let entry = cache.get(&vienna(2), 0.5 * HOUR_MS).unwrap();
+        assert!(entry.is_fresh(0.5 * HOUR_MS));
+        assert!(!entry.is_fresh(2.0 * HOUR_MS));
+        assert!(cache.get(&vienna(2), 49.0 * HOUR_MS).is_none());
+    }
+
+    #[test]
+    fn oldest_entries_are_dropped() {
+        let cache = Cache::default();
+        for days in 1..=MAX_ENTRIES + 1 {
+            cache.insert(vienna(days), Data::default(), days as f64);
+        }
+        assert!(cache.get(&vienna(1), 0.0).is_none());
+        assert!(cache.get(&vienna(2), 0.0).is_some());
+    }
+}
@@ -3,6 +3,27 @@
 use leptos::*;
 use thiserror::Error;
 
+use crate::{
+    ensemble::PrecipitationSpread,
+    listen::Listen,
+    meteogram::Meteogram,
+    nowcast::{Minutely15, Nowcast},
+    moon::MoonDay,
+    planner::PhotoPlanner,
+    storage,
+    sun::{days_since_j2000, format_hour, SunDay, SunTrack},
+    table::{display, HourlyTable, Row},
+    units::Units,
+    weather_code::{WeatherCode, WeatherIcon},
+    zoom::TimeRange,
+};
+
+const SHOW_SUN_KEY: &str = "weather.show_sun";
+/// Metadata of DWD's global ICON model, one of those the forecast blends. The
+/// forecast does not tell which model serves the location, so the run shown is
+/// labelled as ICON's.
+const MODEL_METADATA: &str = "https://api.open-meteo.com/data/dwd_icon/static/meta.json";
+
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct Data {
     latitude: f64,
@@ -12,453 +33,508 @@ pub struct Data {
     timezone: String,
     timezone_abbreviation: String,
     elevation: f64,
+    #[serde(default)]
+    current: Option<Current>,
+    #[serde(default)]
+    minutely_15: Option<Minutely15>,
     hourly: Hourly,
+    /// Only in responses to queries asking for daily values.
+    #[serde(default)]
     daily: Daily,
+    /// Units requested, not part of the API response.
+    #[serde(default)]
+    units: Units,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct ModelMetadata {
+    /// Start of the latest run, in seconds since the Unix epoch.
+    last_run_initialisation_time: f64,
+}
+
+/// Conditions at the time of the request.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct Current {
+    /// Local time of the observation.
+    time: String,
+    temperature_2m: f64,
+    apparent_temperature: f64,
+    relative_humidity_2m: f64,
+    precipitation: f64,
+    weather_code: WeatherCode,
+    wind_speed_10m: f64,
+    wind_direction_10m: f64,
+    wind_gusts_10m: f64,
+    cloud_cover: f64,
+    pressure_msl: f64,
+    is_day: u8,
 }
 
 #[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
 struct Hourly {
     time: Vec<String>,
-    apparent_temperature: Vec<f64>,
-    precipitation_probability: Vec<f64>,
-    precipitation: Vec<f64>,
-    wind_speed_10m: Vec<f64>,
-    wind_direction_10m: Vec<f64>,
+    // Values are missing for some hours, for example at the end of long forecasts
+    apparent_temperature: Vec<Option<f64>>,
+    precipitation_probability: Vec<Option<f64>>,
+    precipitation: Vec<Option<f64>>,
+    wind_speed_10m: Vec<Option<f64>>,
+    wind_direction_10m: Vec<Option<f64>>,
+    #[serde(default)]
+    weather_code: Vec<Option<WeatherCode>>,
+    /// 1 during daylight, 0 at night.
+    #[serde(default)]
+    is_day: Vec<Option<u8>>,
+    #[serde(default)]
+    temperature_2m: Vec<Option<f64>>,
+    #[serde(default)]
+    uv_index: Vec<Option<f64>>,
 }
 
 #[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 struct Daily {
     time: Vec<String>,
-    uv_index_max: Vec<f64>,
+    // Values are missing for some days, like the hourly ones
+    uv_index_max: Vec<Option<f64>>,
+    weather_code: Vec<Option<WeatherCode>>,
+    temperature_2m_max: Vec<Option<f64>>,
+    temperature_2m_min: Vec<Option<f64>>,
+    apparent_temperature_max: Vec<Option<f64>>,
+    apparent_temperature_min: Vec<Option<f64>>,
+    precipitation_sum: Vec<Option<f64>>,
+    precipitation_probability_max: Vec<Option<f64>>,
+    precipitation_hours: Vec<Option<f64>>,
+    wind_speed_10m_max: Vec<Option<f64>>,
+    wind_direction_10m_dominant: Vec<Option<f64>>,
 }
 
 #[derive(Error, Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[error("Failed to load data.\n{reason}")]
 pub struct LoadError {
     reason: String,
+    #[serde(default)]
+    aborted: bool,
+}
+
+impl LoadError {
+    /// The request was cancelled because a newer one replaced it.
+    pub fn aborted() -> Self {
+        LoadError {
+            reason: "the request was replaced by a newer one.".to_string(),
+            aborted: true,
+        }
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.aborted
+    }
 }
 
 impl Data {
     pub fn api_query(
         latitude: f64,
         longitude: f64,
-        forecast_days: usize,) -> String {
-        format!("https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=apparent_temperature,precipitation_probability,precipitation,wind_speed_10m,wind_direction_10m&forecast_days={}&daily=uv_index_max", latitude, longitude, forecast_days)
+        forecast_days: usize,
+        units: Units,) -> String {
+        format!("https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&hourly=apparent_temperature,precipitation_probability,precipitation,wind_speed_10m,wind_direction_10m,weather_code,is_day,temperature_2m,uv_index&forecast_days={}&daily=uv_index_max,weather_code,temperature_2m_max,temperature_2m_min,apparent_temperature_max,apparent_temperature_min,precipitation_sum,precipitation_probability_max,precipitation_hours,wind_speed_10m_max,wind_direction_10m_dominant&current=temperature_2m,apparent_temperature,relative_humidity_2m,precipitation,weather_code,wind_speed_10m,wind_direction_10m,wind_gusts_10m,cloud_cover,pressure_msl,is_day&minutely_15=precipitation,rain,snowfall,lightning_potential&forecast_minutely_15=12&timezone=auto&{}", latitude, longitude, forecast_days, units.api_parameters())
     }
     /// Load data from open-meteo.com
     pub async fn load(
         latitude: f64,
         longitude: f64,
         forecast_days: usize,
+        units: Units,
     ) -> Result<Self, LoadError> {
         let query = Self::api_query(
         latitude,
         longitude,
-        forecast_days);
+        forecast_days,
+        units);
         let resp = reqwest::get(query).await;
         let data = match resp {
             Ok(resp) => resp.json::<Data>().await.map_err(|e| LoadError {
                 reason: format!("failed to parse forecast data.\nCauses:\n\n{}", e),
+                aborted: false,
             }),
             Err(e) => Err(LoadError {
                 reason: format!("failed to retrieve forecast data.\nCauses:\n\n{}", e),
+                aborted: false,
             }),
         };
-        data
+        data.map(|data| data.with_units(units))
+    }
+
+    /// Start of the latest run of the ICON model, in seconds since the Unix epoch.
+    /// It is only nice to know, so failures are logged and leave it out.
+    async fn load_model_run() -> Option<f64> {
+        let metadata = match reqwest::get(MODEL_METADATA).await {
+            Ok(resp) => resp.json::<ModelMetadata>().await,
+            Err(e) => Err(e),
+        };
+        metadata
+            .map_err(|e| log::warn!("failed to load the model run: {e}"))
+            .ok()
+            .map(|metadata| metadata.last_run_initialisation_time)
+    }
+
+    /// Record the units requested, converting locally those the API does not support.
+    fn with_units(mut self, units: Units) -> Self {
+        let current_speeds = self
+            .current
+            .iter_mut()
+            .flat_map(|current| [&mut current.wind_speed_10m, &mut current.wind_gusts_10m]);
+        let speeds = self
+            .hourly
+            .wind_speed_10m
+            .iter_mut()
+            .flatten()
+            .chain(self.daily.wind_speed_10m_max.iter_mut().flatten())
+            .chain(current_speeds);
+        for speed in speeds {
+            *speed = units.wind_speed.convert_api_value(*speed);
+        }
+        self.units = units;
+        self
+    }
+}
+
+impl Hourly {
+    /// One row per hour, for the table view.
+    fn rows(&self) -> Vec<Row> {
+        // Missing values are not a number, which charts leave out
+        let value = |values: &[Option<f64>], i: usize| values.get(i).copied().flatten().unwrap_or(f64::NAN);
+        (0..self.time.len())
+            .map(|i| Row {
+                time: self.time[i].clone(),
+                weather_code: self.weather_code.get(i).copied().flatten().unwrap_or(WeatherCode::Unknown(u8::MAX)),
+                is_day: self.is_day.get(i).copied().flatten().is_none_or(|is_day| is_day == 1),
+                temperature: value(&self.temperature_2m, i),
+                apparent_temperature: value(&self.apparent_temperature, i),
+                precipitation: value(&self.precipitation, i),
+                precipitation_probability: value(&self.precipitation_probability, i),
+                wind_speed: value(&self.wind_speed_10m, i),
+                wind_direction: value(&self.wind_direction_10m, i),
+                uv_index: value(&self.uv_index, i),
+            })
+            .collect()
     }
 }
 
 impl leptos::IntoView for Data {
     fn into_view(self) -> View {
-        let Data { hourly, daily, .. } = self;
-        let Hourly {
-            time,
-            precipitation_probability,
-            precipitation,
-            apparent_temperature,
+        let Data {
+            latitude,
+            longitude,
+            hourly,
+            daily,
+            utc_offset_seconds,
+            units,
+            current,
+            minutely_15,
+            timezone_abbreviation,
             ..
-        } = hourly;
-        let uv_index_max = daily.uv_index_max;
-        let daily_time = daily.time;
-        // let Daily {
-        //     uv_index_max,
-        //     time as daily_time
-        //     ..
-        // } = daily;
-        // Precipitation
-        let precipitation_with_probability = precipitation.into_iter().zip(precipitation_probability.into_iter()).collect::<Vec<_>>();
-        // Wind todo
-
+        } = self;
+        let rows = hourly.rows();
+        let time = hourly.time;
+        // Hours shown in the charts and the table, shared by all of them
+        let hours = time.len();
+        let visible = create_rw_signal(TimeRange::all(hours));
+        let (show_table, set_show_table) = create_signal(false);
+        let hovered = create_rw_signal(None::<String>);
+        // Nights and the course of the sun, computed for the location
+        let sun = time
+            .first()
+            .and_then(|first| days_since_j2000(first, utc_offset_seconds))
+            .map(|start| SunTrack::new(latitude, longitude, start, hours))
+            .unwrap_or_default();
+        let daily_dates = daily.time.clone();
+        let listened_rows = rows.clone();
+        let (show_sun, set_show_sun) = create_signal(storage::get(SHOW_SUN_KEY).unwrap_or(false));
+        create_effect(move |_| storage::set(SHOW_SUN_KEY, &show_sun.get()));
+        // The spread of the precipitation, loaded after the forecast is shown
+        let precipitation_spread = create_rw_signal(Vec::new());
+        {
+            let time = time.clone();
+            spawn_local(async move {
+                let spread = PrecipitationSpread::load(latitude, longitude, hours.div_ceil(24), units).await;
+                if let Some(spread) = spread {
+                    // Unless the forecast was replaced in the meantime
+                    precipitation_spread.try_set(spread.ranges(&time));
+                }
+            });
+        }
+        // Loaded on its own, not to hold the forecast back
+        let model_run = create_rw_signal(None);
+        spawn_local(async move {
+            if let Some(run) = Data::load_model_run().await {
+                model_run.try_set(Some(run));
+            }
+        });
+        let charts = move || {
+            if show_table.get() {
+                let rows = rows.clone();
+                return (move || view! {
+                    <HourlyTable
+                        rows = rows[visible.get().indices(hours)].to_vec()
+                        units = units
+                    />
+                })
+                .into_view();
+            }
+            view! {
+                <div
+                    class="svg_graph meteogram_graph"
+                >
+                    <Meteogram
+                        rows = rows.clone()
+                        units = units
+                        hovered = hovered
+                        visible = visible
+                        sun = sun.clone()
+                        show_sun = show_sun
+                        precipitation_spread = precipitation_spread
+                    />
+                </div>
+            }
+            .into_view()
+        };
 
         view! {
+            {current.map(|current| {
+                let now = current.time.clone();
+                view! {
+                    <CurrentConditions
+                        current = current
+                        units = units
+                        timezone_abbreviation = timezone_abbreviation
+                    />
+                    {minutely_15.map(|minutely_15| view! {
+                        <Nowcast
+                            minutely_15 = minutely_15
+                            now = now
+                            unit = units.precipitation
+                        />
+                    })}
+                }
+            })}
+            <DayCards
+                daily = daily
+                units = units
+                visible = visible
+                hours = hours
+                latitude = latitude
+                longitude = longitude
+                utc_offset_seconds = utc_offset_seconds
+            />
+            <div class="view_selector">
+                <button type="button" aria-pressed=move || (!show_table.get()).to_string() on:click=move |_| set_show_table.set(false)>"Charts 📈"</button>
+                <button type="button" aria-pressed=move || show_table.get().to_string() on:click=move |_| set_show_table.set(true)>"Table 📋"</button>
+                <label>
+                    <input type="checkbox" prop:checked=show_sun on:change=move |ev| set_show_sun.set(event_target_checked(&ev))/>
+                    "Sun elevation 🌄"
+                </label>
+                <Listen
+                    rows = listened_rows
+                    units = units
+                    visible = visible
+                />
+            </div>
             <div
                 class="graph_container"
+                class:table_view=show_table
             >
-                <div
-                    class="svg_graph"
-                >
-                	<Precipitation
-					    precipitation_with_probability = precipitation_with_probability
-                        time = &time
-					/>
-                </div>
-                <div
-                    class="svg_graph"
-                >
-                	<Temperature
-                		temperature = apparent_temperature
-                        time = &time
-                	/>
-                </div>
-                <div
-                    class="svg_graph"
-                >
-                    <UV
-                        uv_index_max = uv_index_max
-                        daily_time = &daily_time
-                    />
-                </div>
+                {charts}
             </div>
+            <PhotoPlanner
+                dates = daily_dates
+                latitude = latitude
+                longitude = longitude
+                utc_offset_seconds = utc_offset_seconds
+            />
             <div>
                 <p>"📅 " {time.first()} " - " {time.last()} </p>
+                {move || model_run.get().map(|model_run| view! {
+                    <p>"⚙ Latest ICON model run: " {crate::offline::local_time(model_run * 1000.0)}</p>
+                })}
             </div>
         }
         .into_view()
     }
 }
 
-#[component]
-fn UV<'a>(uv_index_max: Vec<f64>, daily_time: &'a [String]) -> impl IntoView {
-    const MAX_UV: f64 = 11.0; 
-    const MIN_UV: f64 = 0.0; 
-    const ENJOY_UV: f64 = 2.5;  // below this, You can safely enjoy being outside!
-    const SEEK_UV: f64 = 7.5;   // below this, Seek shade during midday hours! Slip on a shirt, slop on sunscreen and slap on hat!
-                                // above this, Avoid being outside during midday hours! Make sure you seek shade! Shirt, sunscreen and hat are a must! 
-    const LOWER_MARGIN: f64 = 10.0;
-    let uv_size = (daily_time.len()*24, 30.0 + LOWER_MARGIN); // MAX_UV - MIN_UV + LOWER_MARGIN);
-    let uv_to_y = |uv: f64| {
-        30.0 / MAX_UV * 
-        (MAX_UV - uv)
-            .min(MAX_UV - MIN_UV)
-            .max(0.0)
-    };
-    let uv_color_high = (255, 0, 255);
-    let uv_color_low = (0, 255, 0);
-    let uv_to_color = |uv: f64| {
-        format!(
-            "color-mix(in hsl shorter hue, rgb({}, {}, {}) {}%, rgb({}, {}, {}))",
-            uv_color_high.0,
-            uv_color_high.1,
-            uv_color_high.2,
-            100.0 - uv_to_y(uv) / (uv_to_y(MAX_UV) - uv_to_y(MIN_UV)).abs() * 100.0,
-            uv_color_low.0,
-            uv_color_low.1,
-            uv_color_low.2
-        )
-    };
-
-    view! {
-        <svg
-            viewBox={ format!("0 0 {} {}", uv_size.0, uv_size.1) }
-            xmlns="http://www.w3.org/2000/svg"
-            width="100%"
-        >
-            // x-axis
-            <text x="0" y=(uv_to_y(MAX_UV) + 2.0) font-size="2px">{format!("{MAX_UV}")}</text>
-            <line 
-                x1="0" 
-                x2={uv_size.0} 
-                y1={uv_to_y(MAX_UV)}
-                y2={uv_to_y(MAX_UV)}
-                stroke={uv_to_color(MAX_UV)}
-                stroke-width="0.2"
-            >
-                <title>{format!("{MAX_UV}")}</title>
-            </line>
-            <text x="0" y=(uv_to_y(MIN_UV)) font-size="2px">{format!("{MIN_UV}")}</text>
-            <line 
-                x1="0" 
-                x2={uv_size.0} 
-                y1={uv_to_y(0.0)}
-                y2={uv_to_y(0.0)}
-                stroke={uv_to_color(0.0)}
-                stroke-width="0.2"
-            >
-                <title>{format!("{MIN_UV}")}</title>
-            </line>
-            <text x="0" y=(uv_to_y(ENJOY_UV)) font-size="2px">{format!("{ENJOY_UV}")}</text>
-            <line 
-                x1="0" 
-                x2={uv_size.0} 
-                y1={uv_to_y(ENJOY_UV)}
-                y2={uv_to_y(ENJOY_UV)}
-                stroke={uv_to_color(ENJOY_UV)}
-                stroke-width="0.2"
-            >
-                <title>{format!("{ENJOY_UV}")}</title>
-            </line>
-            <text x="0" y=(uv_to_y(SEEK_UV)) font-size="2px">{format!("{SEEK_UV}")}</text>
-            <line 
-                x1="0" 
-                x2={uv_size.0} 
-                y1={uv_to_y(SEEK_UV)}
-                y2={uv_to_y(SEEK_UV)}
-                stroke={uv_to_color(SEEK_UV)}
-                stroke-width="0.2"
-            >
-                <title>{format!("{SEEK_UV}")}</title>
-            </line>
-
-            {
-                uv_index_max
-                .into_iter()
-                .enumerate()
-                .map(|(i, uv)| 
-                    (0..24).map(|j| {
-                        view! { 
-                            <circle 
-                                cx={i * 24 + j} 
-                                cy={uv_to_y(uv)}
-                                r="0.5" 
-                                fill={uv_to_color(uv)}
-                            >
-                                <title>{uv}</title>
-                            </circle>
-                        }
-                    }).collect_view()
-                )
-                .collect_view()
-            }
-        </svg>
-        <h2>{"UV ☀"}</h2>      
+/// English name of the weekday of an ISO 8601 date, like "2023-11-10".
+pub(crate) fn weekday(date: &str) -> Option<&'static str> {
+    const WEEKDAYS: [&str; 7] = [
+        "Sunday",
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+    ];
+    // Sakamoto's method
+    const MONTH_OFFSETS: [usize; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+    let mut parts = date.get(..10)?.split('-').map(|part| part.parse::<usize>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    if !(1..=12).contains(&month) {
+        return None;
     }
+    let year = if month < 3 { year - 1 } else { year };
+    let index = (year + year / 4 - year / 100 + year / 400 + MONTH_OFFSETS[month - 1] + day) % 7;
+    Some(WEEKDAYS[index])
 }
 
-
-#[component]
-fn Temperature<'a>(temperature: Vec<f64>, time: &'a [String]) -> impl IntoView {
-    const MAX_TEMPERATURE: f64 = 30.; 
-    const MIN_TEMPERATURE: f64 = -10.; 
-    let temperature_size = (temperature.len(), MAX_TEMPERATURE - MIN_TEMPERATURE); // We limit to very hot and very cold
-    let temperature_to_y = |temperature: f64| {
-        (MAX_TEMPERATURE - temperature)
-            .min(MAX_TEMPERATURE - MIN_TEMPERATURE)
-            .max(0.0)
-    };
-    let temperature_color_high = (255, 0, 0);
-    let temperature_color_low = (0, 0, 255);
-    let temperature_to_color = |temperature: f64| {
-        format!(
-            "color-mix(in oklab, rgb({}, {}, {}) {}%, rgb({}, {}, {}))",
-            temperature_color_high.0,
-            temperature_color_high.1,
-            temperature_color_high.2,
-            100.0 - temperature_to_y(temperature) / (MAX_TEMPERATURE - MIN_TEMPERATURE) * 100.0,
-            temperature_color_low.0,
-            temperature_color_low.1,
-            temperature_color_low.2
-        )
-    };
-
-    view! {
-	    <svg
-	        viewBox={ format!("0 0 {} {}", temperature_size.0, temperature_size.1) }
-	        xmlns="http://www.w3.org/2000/svg"
-	        width="100%"
-	    >
-	        { 
-	            let mut view = Vec::new();
-	            let mut iter = temperature.iter();
-	            let mut counter = 0;
-	            let color = &temperature_to_color(MAX_TEMPERATURE);
-	            while iter.nth(8).is_some() {
-                    let day = &time.get(counter * 24).map(|s| &s[8..10]).unwrap_or("??");
-	                view.push( view!{
-                        <text x={8 + counter * 24} y=2.0 font-size="2px">{format!("{day}/8:00")}</text>
-	                    <line x1={8 + counter * 24} x2={8 + counter * 24} y1=0 y2={temperature_size.1} stroke={color} stroke-width="0.1">
-	                        <title>"8:00"</title>
-	                    </line>
-	                });
-	                if iter.nth(11).is_some() {
-	                    view.push( view!{
-                            <text x={20 + counter * 24} y=2.0 font-size="2px">{format!("{day}/20:00")}</text>
-	                        <line x1={20 + counter * 24} x2={20 + counter * 24} y1=0 y2={temperature_size.1} stroke={color} stroke-width="0.1">
-	                            <title>"20:00"</title>
-	                        </line>
-	                    })
-	                }
-	                counter += 1;
-	            }
-	            view
-	        }
-	        // x-axis
-            <text x="0" y=(temperature_to_y(MAX_TEMPERATURE) + 2.0) font-size="2px">{format!("{MAX_TEMPERATURE}°C")}</text>
-	        <line 
-	            x1="0" 
-	            x2={temperature_size.0} 
-	            y1={temperature_to_y(MAX_TEMPERATURE)}
-	            y2={temperature_to_y(MAX_TEMPERATURE)}
-	            stroke={temperature_to_color(MAX_TEMPERATURE)}
-	            stroke-width="0.2"
-	        >
-	        	<title>{format!("{MAX_TEMPERATURE}°C")}</title>
-	    	</line>
-            <text x="0" y=(temperature_to_y(0.0)) font-size="2px">{format!("0°C")}</text>
-	        <line 
-	            x1="0" 
-	            x2={temperature_size.0} 
-	            y1={temperature_to_y(0.0)}
-	            y2={temperature_to_y(0.0)}
-	            stroke={temperature_to_color(0.0)}
-	            opacity="0.5" 
-	            stroke-width="0.1"
-	        >
-	        	<title>"0°C"</title>
-	    	</line>
-            <text x="0" y=(temperature_to_y(5.0)) font-size="2px">{format!("5°C")}</text>
-	        <line 
-	            x1="0" 
-	            x2={temperature_size.0} 
-	            y1={temperature_to_y(5.0)}
-	            y2={temperature_to_y(5.0)}
-	            stroke={temperature_to_color(5.0)}
-	            opacity="0.8" 
-	            stroke-width="0.1"
-	        >
-	        	<title>"5°C"</title>
-	    	</line>
-            <text x="0" y=(temperature_to_y(10.0)) font-size="2px">{format!("10°C")}</text>
-	        <line 
-	            x1="0" 
-	            x2={temperature_size.0} 
-	            y1={temperature_to_y(10.0)}
-	            y2={temperature_to_y(10.0)}
-	            stroke={temperature_to_color(10.0)}
-	            opacity="0.8" 
-	            stroke-width="0.1"
-	        >
-	        	<title>"10°C"</title>
-	    	</line>
-            <text x="0" y=(temperature_to_y(MIN_TEMPERATURE)) font-size="2px">{format!("{MIN_TEMPERATURE}°C")}</text>
-	        <line 
-	            x1="0" 
-	            x2={temperature_size.0} 
-	            y1={temperature_to_y(MIN_TEMPERATURE)} 
-	            y2={temperature_to_y(MIN_TEMPERATURE)}
-	            stroke={temperature_to_color(MIN_TEMPERATURE)} 
-	            opacity="0.1" 
-	            stroke-width="0.2"
-	        >
-	        	<title>{format!("{MIN_TEMPERATURE}°C")}</title>
-	    	</line>
-	        {temperature
-	            .into_iter()
-	            .enumerate()
-	            .map(|(i, temperature)| 
-	                view! { 
-	                    <circle 
-	                        cx={i} 
-	                        cy={temperature_to_y(temperature)}
-	                        r="0.5" 
-	                        fill={temperature_to_color(temperature)}
-	                    >
-	                        <title>{temperature} "°C"</title>
-	                    </circle>
-	                }
-	            )
-	            .collect_view()
-	        }
-	    </svg>
-	    <h2>{"Temperature 🌡"}</h2>    	
+/// Sunrise and sunset, or the polar day or night without them.
+fn sun_times(sun: &SunDay) -> String {
+    match (sun.sunrise, sun.sunset) {
+        (None, None) if sun.day_length > 0.0 => "☀️ Midnight sun".to_string(),
+        (None, None) => "🌌 Polar night".to_string(),
+        (sunrise, sunset) => format!(
+            "🌅 {} 🌇 {}",
+            sunrise.map(format_hour).unwrap_or("–".to_string()),
+            sunset.map(format_hour).unwrap_or("–".to_string()),
+        ),
     }
 }
-#[component]
-fn Precipitation<'a>(precipitation_with_probability: Vec<(f64, f64)>, time: &'a [String]) -> impl IntoView {
-	const MAX_PRECIPITATION: f64 = 30.0; // mm
-	const LOWER_MARGIN: f64 = 10.0; // mm
 
-	let (precipitation, precipitation_probability): (Vec<_>, Vec<_>) = precipitation_with_probability.into_iter().unzip();
-    let precipitation_size = (precipitation.len() as f64, MAX_PRECIPITATION + LOWER_MARGIN);
-    let precipitation_to_y = |mm: f64| MAX_PRECIPITATION - mm.min(MAX_PRECIPITATION);
-    let precipitation_color = (78, 104, 129);
-    let color = &format!("rgb({}, {}, {})", precipitation_color.0, precipitation_color.1, precipitation_color.2);
+/// One card per day. Clicking a card zooms the charts to that day.
+#[component]
+fn DayCards(
+    daily: Daily,
+    units: Units,
+    visible: RwSignal<TimeRange>,
+    hours: usize,
+    latitude: f64,
+    longitude: f64,
+    utc_offset_seconds: f64,
+) -> impl IntoView {
+    let all = TimeRange::all(hours);
+    let day = move |i: usize| TimeRange::new((i * 24) as f64, ((i + 1) * 24).min(hours) as f64);
+    let temperature_unit = units.temperature.symbol();
+    // Missing values are shown as a dash
+    let value = |values: &[Option<f64>], i: usize| values.get(i).copied().flatten();
+    let text = move |values: &[Option<f64>], i: usize| display(value(values, i).unwrap_or(f64::NAN));
 
     view! {
-        <svg
-            viewBox={ format!("0 0 {} {}", precipitation_size.0, precipitation_size.1) }
-            xmlns="http://www.w3.org/2000/svg"
-            width="100%"
-        >
-        	// Vertical lines
-            {
-                let mut view = Vec::new();
-                let mut iter = precipitation.iter();
-                let mut counter = 0;
-                while iter.nth(8).is_some() {
-                    let day = &time.get(counter * 24).map(|s| &s[8..10]).unwrap_or("??");
-                    view.push( view!{
-                        <text x={8 + counter * 24} y=2.0 font-size="2px">{format!("{day}/8:00")}</text>
-                        <line x1={8 + counter * 24} x2={8 + counter * 24} y1=0 y2={precipitation_size.1} stroke={color} stroke-width="0.1">
-                            <title>"8:00"</title>
-                        </line>
-                    });
-                    if iter.nth(11).is_some() {
-                        view.push( view!{
-                            <text x={20 + counter * 24} y=2.0 font-size="2px">{format!("{day}/20:00")}</text>
-                            <line x1={20 + counter * 24} x2={20 + counter * 24} y1=0 y2={precipitation_size.1} stroke={color} stroke-width="0.1">
-                                <title>"20:00"</title>
-                            </line>
-                        })
-                    }
-                    counter += 1;
-                }
-                view
-            }
-            // Horizontal lines
-            <text x="0" y=(precipitation_to_y(0.0)) font-size="2px">{format!("Nothing")}</text>
-            <line x1="0" x2={precipitation_size.0} y1={precipitation_to_y(0.0)} y2={precipitation_to_y(0.0)} opacity={0.2} stroke={color} stroke-width="0.1">
-            	<title>"Nothing"</title>
-            </line>
-            <text x="0" y=(precipitation_to_y(2.5)) font-size="2px">{format!("Light")}</text>
-            <line x1="0" x2={precipitation_size.0} y1={precipitation_to_y(2.5)} y2={precipitation_to_y(2.5)} opacity={0.4} stroke={color} stroke-width="0.1">
-            	<title>"Light"</title>
-            </line>
-            <text x="0" y=(precipitation_to_y(7.6)) font-size="2px">{format!("Moderate")}</text>
-            <line x1="0" x2={precipitation_size.0} y1={precipitation_to_y(7.6)} y2={precipitation_to_y(7.6)} opacity={0.6} stroke={color} stroke-width="0.1">
-            	<title>"Moderate"</title>
-            </line>
-            <text x="0" y=(precipitation_to_y(50.0) + 2.0) font-size="2px">{format!("Heavy")}</text>
-            <line x1="0" x2={precipitation_size.0} y1={precipitation_to_y(50.0)} y2={precipitation_to_y(50.0)} stroke={color} stroke-width="0.1">
-            	<title>"Heavy"</title>
-            </line>
-            // Point series
-            {precipitation
-                .into_iter()
-                .zip(precipitation_probability.into_iter())
-                .enumerate()
-                .map(|(i, (mm, probability))|
+        <div class="day_cards">
+            {(0..daily.time.len())
+                .map(|i| {
+                    let date = daily.time[i].clone();
+                    let code = daily.weather_code.get(i).copied().flatten().unwrap_or(WeatherCode::Unknown(u8::MAX));
+                    let probability = value(&daily.precipitation_probability_max, i);
+                    let sun = SunDay::new(latitude, longitude, &date, utc_offset_seconds);
+                    let moon = MoonDay::new(latitude, longitude, &date, utc_offset_seconds);
                     view! {
-                        <circle
-                            cx={i}
-                            cy={precipitation_to_y(mm)}
-                            opacity={probability / 100.0}
-                            r={ if mm > 0.0 {0.5} else {0.3} }
-                            fill={color}
+                        <button
+                            type="button"
+                            class="day_card"
+                            class:selected=move || visible.get() == day(i)
+                            aria-pressed=move || (visible.get() == day(i)).to_string()
+                            on:click=move |_| visible.update(|visible| {
+                                *visible = if *visible == day(i) { all } else { day(i) }
+                            })
                         >
-                            <title>{mm} "mm with " {probability} "%"</title>
-                        </circle>
+                            <strong>{weekday(&date).unwrap_or_default()} " " {date.get(8..10).unwrap_or_default().to_string()}</strong>
+                            <svg viewBox="0 0 10 10" xmlns="http://www.w3.org/2000/svg" width="3em" height="3em">
+                                <WeatherIcon code is_day=true x=0.0 y=0.0 size=10.0/>
+                            </svg>
+                            <span class="day_card_temperature">
+                                {text(&daily.temperature_2m_max, i)} " / " {text(&daily.temperature_2m_min, i)} {temperature_unit}
+                            </span>
+                            <span>
+                                "Feels " {text(&daily.apparent_temperature_max, i)} " / " {text(&daily.apparent_temperature_min, i)} {temperature_unit}
+                            </span>
+                            <span>
+                                "💧 " {text(&daily.precipitation_sum, i)} " " {units.precipitation.symbol()}
+                                {probability.map(|p| format!(" ({p}%)"))}
+                                " in " {text(&daily.precipitation_hours, i)} " h"
+                            </span>
+                            <span>
+                                "💨 " {value(&daily.wind_direction_10m_dominant, i).map(wind_arrow)} " "
+                                {text(&daily.wind_speed_10m_max, i)} " " {units.wind_speed.symbol()}
+                            </span>
+                            {sun.map(|sun| view! {
+                                <span title=format!("Day length {}", format_hour(sun.day_length))>{sun_times(&sun)}</span>
+                            })}
+                            {moon.map(|moon| view! {
+                                <span title=format!("{:.0}% lit", moon.phase.illumination * 100.0)>
+                                    {moon.phase.emoji()} " " {moon.phase.name()}
+                                </span>
+                            })}
+                        </button>
                     }
-                )
+                })
                 .collect_view()
             }
-        </svg>
-        <h2>{"Precipitation 🌦"}</h2>
+        </div>
     }
 }
 
-// const EMOJI: [char; 13] = [
-//     '🗺', '🌡', '🧭', '🌣', '🌤', '🌥', '☁', '🌦', '⛈', '🌧', '🌩', '🌨', '🌪',
-// ];
+/// Arrow pointing where the wind blows to, from the direction it comes from in degrees.
+pub(crate) fn wind_arrow(direction: f64) -> impl IntoView {
+    view! {
+        <span
+            class="wind_arrow"
+            style={format!("display: inline-block; transform: rotate({direction}deg)")}
+            title={format!("{direction}°")}
+        >
+            "↓"
+        </span>
+    }
+}
+
+/// Headline card: what the weather is like right now.
+#[component]
+fn CurrentConditions(current: Current, units: Units, timezone_abbreviation: String) -> impl IntoView {
+    let Current {
+        time,
+        temperature_2m,
+        apparent_temperature,
+        relative_humidity_2m,
+        precipitation,
+        weather_code,
+        wind_speed_10m,
+        wind_direction_10m,
+        wind_gusts_10m,
+        cloud_cover,
+        pressure_msl,
+        is_day,
+    } = current;
+    let temperature_unit = units.temperature.symbol();
+    let wind_unit = units.wind_speed.symbol();
+    // Times are local to the location, as "2023-11-10T14:15"
+    let observed_at = time.split_once('T').map_or(time.clone(), |(_, hour)| hour.to_string());
+
+    view! {
+        <h2>{"Now 📍"}</h2>
+        <div class="current">
+            <svg viewBox="0 0 10 10" xmlns="http://www.w3.org/2000/svg" width="4em" height="4em">
+                <WeatherIcon code=weather_code is_day={is_day == 1} x=0.0 y=0.0 size=10.0/>
+            </svg>
+            <div>
+                <p class="current_temperature">{temperature_2m} {temperature_unit}</p>
+                <p>{weather_code.description()} ", feels like " {apparent_temperature} {temperature_unit}</p>
+            </div>
+            <dl>
+                <dt>"Wind"</dt>
+                <dd>
+                    {wind_arrow(wind_direction_10m)} " " {wind_speed_10m} " " {wind_unit}
+                    " (gusts " {wind_gusts_10m} " " {wind_unit} ")"
+                </dd>
+                <dt>"Precipitation"</dt>
+                <dd>{precipitation} " " {units.precipitation.symbol()}</dd>
+                <dt>"Humidity"</dt>
+                <dd>{relative_humidity_2m} "%"</dd>
+                <dt>"Cloud cover"</dt>
+                <dd>{cloud_cover} "%"</dd>
+                <dt>"Pressure"</dt>
+                <dd>{pressure_msl} " hPa"</dd>
+            </dl>
+            <p class="observed_at">"Observed at " {observed_at} " " {timezone_abbreviation}</p>
+        </div>
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -468,7 +544,7 @@ mod tests {
     #[test]
     fn test_load_data() -> Result<(), LoadError> {
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let _data = rt.block_on(Data::load(1., 1., 1))?;
+        let _data = rt.block_on(Data::load(1., 1., 1, Units::default()))?;
         Ok(())
     }
 
@@ -795,7 +871,137 @@ mod tests {
 }
 }
 		"#;
-        let _data: Data = serde_json::from_str(&raw_data)?;
+        let _data: Data = serde_json::from_str(raw_data)?;
+        Ok(())
+    }
+
+    #[test]
+    fn missing_daily_values() -> Result<(), serde_json::Error> {
+        let data: Data = serde_json::from_str(
+            r#"{
+                "latitude": 48.3,
+                "longitude": 16.3,
+                "generationtime_ms": 0.1,
+                "utc_offset_seconds": 3600,
+                "timezone": "Europe/Vienna",
+                "timezone_abbreviation": "CET",
+                "elevation": 200.0,
+                "hourly": {
+                    "time": ["2023-11-10T00:00"],
+                    "apparent_temperature": [1.5],
+                    "precipitation_probability": [0],
+                    "precipitation": [0.0],
+                    "wind_speed_10m": [3.0],
+                    "wind_direction_10m": [180]
+                }
+            }"#,
+        )?;
+        assert!(data.daily.time.is_empty());
+        assert_eq!(data.hourly.rows().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn null_values() -> Result<(), serde_json::Error> {
+        let data: Data = serde_json::from_str(
+            r#"{
+                "latitude": 48.3,
+                "longitude": 16.3,
+                "generationtime_ms": 0.1,
+                "utc_offset_seconds": 3600,
+                "timezone": "Europe/Vienna",
+                "timezone_abbreviation": "CET",
+                "elevation": 200.0,
+                "hourly": {
+                    "time": ["2023-11-10T00:00", "2023-11-10T01:00"],
+                    "apparent_temperature": [1.5, null],
+                    "precipitation_probability": [0, null],
+                    "precipitation": [0.0, null],
+                    "wind_speed_10m": [3.0, null],
+                    "wind_direction_10m": [180, null],
+                    "weather_code": [3, null],
+                    "is_day": [0, null]
+                },
+                "daily": {
+                    "time": ["2023-11-10", "2023-11-11"],
+                    "uv_index_max": [1.2, null],
+                    "weather_code": [3, null],
+                    "temperature_2m_max": [9.0, null],
+                    "temperature_2m_min": [1.0, null],
+                    "apparent_temperature_max": [7.5, null],
+                    "apparent_temperature_min": [-1.0, null],
+                    "precipitation_sum": [0.4, null],
+                    "precipitation_probability_max": [30, null],
+                    "precipitation_hours": [2.0, null],
+                    "wind_speed_10m_max": [12.0, null],
+                    "wind_direction_10m_dominant": [200, null]
+                }
+            }"#,
+        )?;
+        let rows = data.hourly.rows();
+        assert_eq!(rows[0].weather_code, WeatherCode::Overcast);
+        assert!(!rows[0].is_day);
+        assert_eq!(rows[1].weather_code, WeatherCode::Unknown(u8::MAX));
+        assert!(rows[1].is_day);
+        assert_eq!(data.daily.temperature_2m_max, [Some(9.0), None]);
+        assert_eq!(data.daily.weather_code, [Some(WeatherCode::Overcast), None]);
+        // Wind speeds converted to other units stay missing
+        let data = data.with_units(Units {
+            wind_speed: crate::units::WindSpeedUnit::Beaufort,
+            ..Units::METRIC
+        });
+        assert_eq!(data.daily.wind_speed_10m_max[1], None);
+        Ok(())
+    }
+
+    #[test]
+    fn missing_hourly_values() -> Result<(), serde_json::Error> {
+        let hourly: Hourly = serde_json::from_str(
+            r#"{
+                "time": ["2023-11-10T00:00", "2023-11-10T01:00"],
+                "apparent_temperature": [1.5, null],
+                "precipitation_probability": [null, null],
+                "precipitation": [0.0, 0.2],
+                "wind_speed_10m": [3.0, 4.0],
+                "wind_direction_10m": [180, 190]
+            }"#,
+        )?;
+        let rows = hourly.rows();
+        assert_eq!(rows[0].apparent_temperature, 1.5);
+        assert!(rows[1].apparent_temperature.is_nan());
+        assert!(rows[1].temperature.is_nan());
+        Ok(())
+    }
+
+    #[test]
+    fn weekdays() {
+        assert_eq!(weekday("2023-11-10"), Some("Friday"));
+        assert_eq!(weekday("2024-02-29"), Some("Thursday"));
+        assert_eq!(weekday("2000-01-01T00:00"), Some("Saturday"));
+        assert_eq!(weekday("2023-13-01"), None);
+    }
+
+    #[test]
+    fn current_deserialization() -> Result<(), serde_json::Error> {
+        let raw_current = r#"
+{
+"time": "2023-11-10T14:15",
+"interval": 900,
+"temperature_2m": 9.8,
+"apparent_temperature": 7.2,
+"relative_humidity_2m": 71,
+"precipitation": 0.0,
+"weather_code": 3,
+"wind_speed_10m": 11.2,
+"wind_direction_10m": 252,
+"wind_gusts_10m": 25.6,
+"cloud_cover": 100,
+"pressure_msl": 1012.4,
+"is_day": 1
+}
+		"#;
+        let current: Current = serde_json::from_str(raw_current)?;
+        assert_eq!(current.weather_code, WeatherCode::Overcast);
         Ok(())
     }
 }
@@ -0,0 +1,373 @@
+//! Text alternatives of the charts, for screen readers: summaries of the series
+//! shown, sentences reading out the values of one hour, and data tables.
+
+use leptos::*;
+
+use crate::{
+    data::weekday,
+    table::{display, Row},
+    units::Units,
+};
+
+/// Names of the directions the wind can blow from, clockwise from north.
+const COMPASS: [&str; 8] = [
+    "north",
+    "north-east",
+    "east",
+    "south-east",
+    "south",
+    "south-west",
+    "west",
+    "north-west",
+];
+
+/// UV index below which one can safely enjoy being outside.
+pub const ENJOY_UV: f64 = 2.5;
+/// UV index below which seeking shade during midday hours is enough. Above it,
+/// one should avoid being outside then.
+pub const SEEK_UV: f64 = 7.5;
+
+/// Direction the wind blows from, like "south-west".
+pub fn compass(direction: f64) -> &'static str {
+    COMPASS[(direction.rem_euclid(360.0) / 45.0).round() as usize % COMPASS.len()]
+}
+
+/// Labels of local times like "2023-11-10T14:00": the hour alone, or with its
+/// weekday when they span several days.
+fn time_labels<'a>(times: &[&'a str]) -> impl Fn(&'a str) -> String {
+    let day = |time: &'a str| time.split_once('T').map_or(time, |(day, _)| day);
+    let one_day = times.windows(2).all(|w| day(w[0]) == day(w[1]));
+    move |time| {
+        let (date, hour) = time.split_once('T').unwrap_or((time, ""));
+        if one_day {
+            hour.to_string()
+        } else {
+            format!("{} {hour}", weekday(date).unwrap_or(date))
+        }
+    }
+}
+
+/// How a series goes, through its lowest and highest values, like "Temperature
+/// rises from 1°C at 00:00 to a high of 9°C at 14:00, then falls to 3°C at 23:00."
+///
+/// Points are (local time, value), and missing values are left out.
+pub fn trend(name: &str, points: &[(&str, f64)], unit: &str) -> String {
+    let points: Vec<_> = points
+        .iter()
+        .copied()
+        .filter(|(_, value)| value.is_finite())
+        .collect();
+    let label = time_labels(&points.iter().map(|(time, _)| *time).collect::<Vec<_>>());
+    let value = |i: usize| format!("{}{unit}", display(points[i].1));
+    let Some(last) = points.len().checked_sub(1) else {
+        return format!("{name}: no data.");
+    };
+    // First of the lowest and of the highest values
+    let (mut low, mut high) = (0, 0);
+    for (i, &(_, v)) in points.iter().enumerate() {
+        if v < points[low].1 {
+            low = i;
+        }
+        if v > points[high].1 {
+            high = i;
+        }
+    }
+    if points[low].1 == points[high].1 {
+        return format!(
+            "{name} stays at {} from {} to {}.",
+            value(0),
+            label(points[0].0),
+            label(points[last].0)
+        );
+    }
+    let mut sentence = String::new();
+    let mut previous = 0;
+    for i in [low.min(high), low.max(high), last] {
+        if i == previous || points[i].1 == points[previous].1 {
+            continue;
+        }
+        let verb = if points[i].1 > points[previous].1 {
+            "rises"
+        } else {
+            "falls"
+        };
+        let extreme = if i == high {
+            "a high of "
+        } else if i == low {
+            "a low of "
+        } else {
+            ""
+        };
+        let to = format!("to {extreme}{} at {}", value(i), label(points[i].0));
+        if sentence.is_empty() {
+            sentence = format!(
+                "{name} {verb} from {} at {} {to}",
+                value(0),
+                label(points[0].0)
+            );
+        } else {
+            sentence += &format!(", then {verb} {to}");
+        }
+        previous = i;
+    }
+    sentence + "."
+}
+
+/// Summary of the precipitation over some hours, like "3.2 mm of precipitation
+/// in total, the most at 15:00 with 1.8 mm. Chance of up to 80% at 14:00."
+pub fn precipitation(rows: &[Row], units: Units) -> String {
+    let label = time_labels(&rows.iter().map(|row| row.time.as_str()).collect::<Vec<_>>());
+    let highest = |value: fn(&Row) -> f64| {
+        rows.iter()
+            .filter(|row| value(row).is_finite())
+            .fold(None::<&Row>, |highest, row| match highest {
+                Some(highest) if value(highest) >= value(row) => Some(highest),
+                _ => Some(row),
+            })
+    };
+    let unit = units.precipitation.symbol();
+    let total: f64 = rows
+        .iter()
+        .map(|row| row.precipitation)
+        .filter(|p| p.is_finite())
+        .sum();
+    let amount = match highest(|row| row.precipitation) {
+        Some(wettest) if total > 0.0 => format!(
+            "{total:.1} {unit} of precipitation in total, the most at {} with {} {unit}.",
+            label(&wettest.time),
+            display(wettest.precipitation)
+        ),
+        _ => "No precipitation expected.".to_string(),
+    };
+    match highest(|row| row.precipitation_probability) {
+        Some(likeliest) if likeliest.precipitation_probability > 0.0 => format!(
+            "{amount} Chance of up to {}% at {}.",
+            display(likeliest.precipitation_probability),
+            label(&likeliest.time)
+        ),
+        _ => amount,
+    }
+}
+
+/// What to do under a UV index.
+pub fn uv_advice(uv: f64) -> &'static str {
+    if uv < ENJOY_UV {
+        "enjoy being outside"
+    } else if uv < SEEK_UV {
+        "seek shade during midday hours"
+    } else {
+        "avoid being outside during midday hours"
+    }
+}
+
+/// Highest UV index over some hours, like "UV index peaks at 6 at 13:00: seek
+/// shade during midday hours."
+pub fn uv(rows: &[Row]) -> String {
+    let label = time_labels(&rows.iter().map(|row| row.time.as_str()).collect::<Vec<_>>());
+    // The first of the highest
+    let peak = rows.iter().filter(|row| row.uv_index.is_finite()).fold(
+        None::<&Row>,
+        |peak, row| match peak {
+            Some(peak) if peak.uv_index >= row.uv_index => Some(peak),
+            _ => Some(row),
+        },
+    );
+    match peak {
+        Some(peak) => format!(
+            "UV index peaks at {} at {}: {}.",
+            display(peak.uv_index),
+            label(&peak.time),
+            uv_advice(peak.uv_index)
+        ),
+        None => "No UV index forecast.".to_string(),
+    }
+}
+
+/// Summary of the meteogram over some hours.
+pub fn meteogram(rows: &[Row], units: Units) -> String {
+    let series = |value: fn(&Row) -> f64| {
+        rows.iter()
+            .map(|row| (row.time.as_str(), value(row)))
+            .collect::<Vec<_>>()
+    };
+    [
+        trend(
+            "Temperature",
+            &series(|row| row.temperature),
+            units.temperature.symbol(),
+        ),
+        precipitation(rows, units),
+        trend(
+            "Wind",
+            &series(|row| row.wind_speed),
+            &format!(" {}", units.wind_speed.symbol()),
+        ),
+        uv(rows),
+    ]
+    .join(" ")
+}
+
+/// Every value of one hour, read out when it is reached with the keyboard.
+pub fn hour(row: &Row, units: Units) -> String {
+    let (day, hour) = row.time.split_once('T').unwrap_or((&row.time, ""));
+    let temperature = units.temperature.symbol();
+    format!(
+        "{} {hour}: {}, {}{temperature}, feels like {}{temperature}. {} {} of precipitation, {}% chance. Wind {} {} from the {}. UV index {}.",
+        weekday(day).unwrap_or(day),
+        row.weather_code.description(),
+        display(row.temperature),
+        display(row.apparent_temperature),
+        display(row.precipitation),
+        units.precipitation.symbol(),
+        display(row.precipitation_probability),
+        display(row.wind_speed),
+        units.wind_speed.symbol(),
+        compass(row.wind_direction),
+        display(row.uv_index),
+    )
+}
+
+/// Table of the values of a chart, only shown to screen readers.
+///
+/// The first value of each row is its header.
+#[component]
+pub fn HiddenTable(
+    caption: String,
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+) -> impl IntoView {
+    view! {
+        <table class="visually_hidden">
+            <caption>{caption}</caption>
+            <thead>
+                <tr>
+                    {headers.into_iter().map(|header| view! { <th scope="col">{header}</th> }).collect_view()}
+                </tr>
+            </thead>
+            <tbody>
+                {rows.into_iter().map(|row| {
+                    let mut cells = row.into_iter();
+                    view! {
+                        <tr>
+                            <th scope="row">{cells.next()}</th>
+                            {cells.map(|cell| view! { <td>{cell}</td> }).collect_view()}
+                        </tr>
+                    }
+                }).collect_view()}
+            </tbody>
+        </table>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather_code::WeatherCode;
+
+    fn row(time: &str, temperature: f64, precipitation: f64, probability: f64) -> Row {
+        Row {
+            time: time.to_string(),
+            weather_code: WeatherCode::ClearSky,
+            is_day: true,
+            temperature,
+            apparent_temperature: temperature,
+            precipitation,
+            precipitation_probability: probability,
+            wind_speed: 10.0,
+            wind_direction: 225.0,
+            uv_index: 0.0,
+        }
+    }
+
+    fn points(values: &[f64]) -> Vec<(String, f64)> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| (format!("2023-11-10T{i:02}:00"), v))
+            .collect()
+    }
+
+    fn trend_of(values: &[f64]) -> String {
+        let points = points(values);
+        let points: Vec<_> = points.iter().map(|(t, v)| (t.as_str(), *v)).collect();
+        trend("Temperature", &points, "°C")
+    }
+
+    #[test]
+    fn trends() {
+        let day: Vec<f64> = (0..24)
+            .map(|i| {
+                if i <= 14 {
+                    1.0 + i as f64 * 8.0 / 14.0
+                } else {
+                    9.0 - (i - 14) as f64 * 6.0 / 9.0
+                }
+            })
+            .collect();
+        assert_eq!(
+            trend_of(&day),
+            "Temperature rises from 1°C at 00:00 to a high of 9°C at 14:00, then falls to 3°C at 23:00."
+        );
+        assert_eq!(
+            trend_of(&[5.0, 1.0, f64::NAN, 9.0, 9.0]),
+            "Temperature falls from 5°C at 00:00 to a low of 1°C at 01:00, then rises to a high of 9°C at 03:00."
+        );
+        assert_eq!(
+            trend_of(&[2.0, 2.0]),
+            "Temperature stays at 2°C from 00:00 to 01:00."
+        );
+        assert_eq!(trend_of(&[f64::NAN]), "Temperature: no data.");
+        // Weekdays tell the hours of different days apart
+        let points = [("2023-11-10T23:00", 1.0), ("2023-11-11T00:00", 2.0)];
+        assert_eq!(
+            trend("Temperature", &points, "°C"),
+            "Temperature rises from 1°C at Friday 23:00 to a high of 2°C at Saturday 00:00."
+        );
+    }
+
+    #[test]
+    fn precipitation_summary() {
+        let rows = [
+            row("2023-11-10T14:00", 5.0, 0.0, 80.0),
+            row("2023-11-10T15:00", 5.0, 1.8, 60.0),
+            row("2023-11-10T16:00", 5.0, 1.4, 20.0),
+        ];
+        assert_eq!(
+            precipitation(&rows, Units::METRIC),
+            "3.2 mm of precipitation in total, the most at 15:00 with 1.8 mm. Chance of up to 80% at 14:00."
+        );
+        let dry = [row("2023-11-10T14:00", 5.0, 0.0, 0.0)];
+        assert_eq!(
+            precipitation(&dry, Units::METRIC),
+            "No precipitation expected."
+        );
+    }
+
+    #[test]
+    fn uv_summary() {
+        let rows: Vec<Row> = [1.0, 6.0, f64::NAN, 6.0, 3.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &uv)| Row {
+                uv_index: uv,
+                ..row(&format!("2023-11-10T{:02}:00", 10 + i), 9.0, 0.0, 0.0)
+            })
+            .collect();
+        assert_eq!(
+            uv(&rows),
+            "UV index peaks at 6 at 11:00: seek shade during midday hours."
+        );
+        assert_eq!(uv(&rows[2..3]), "No UV index forecast.");
+        assert_eq!(uv_advice(8.0), "avoid being outside during midday hours");
+    }
+
+    #[test]
+    fn hours() {
+        assert_eq!(compass(225.0), "south-west");
+        assert_eq!(compass(350.0), "north");
+        assert_eq!(
+            hour(&row("2023-11-10T14:00", 9.0, 0.2, 40.0), Units::METRIC),
+            "Friday 14:00: Clear sky, 9°C, feels like 9°C. 0.2 mm of precipitation, 40% chance. Wind 10 km/h from the south-west. UV index 0."
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Downsampling of long series, to draw no more points than there are pixels.
+//!
+//! Both methods return the indices of the points kept, in order, so that other
+//! values of the same hours can be looked up. Missing values (not finite) are
+//! never averaged away: the first of each run of them is kept, to leave a gap.
+
+/// Indices of about `threshold` points keeping the shape of a line, with the
+/// Largest-Triangle-Three-Buckets algorithm.
+///
+/// Points are (x, y), ordered by x. The first and last points of every run of
+/// finite values are always kept.
+pub fn lttb(points: &[(f64, f64)], threshold: usize) -> Vec<usize> {
+    if points.len() <= threshold.max(2) {
+        return (0..points.len()).collect();
+    }
+    let finite = points.iter().filter(|(_, y)| y.is_finite()).count().max(1);
+    let mut kept = Vec::with_capacity(threshold);
+    let mut start = 0;
+    while start < points.len() {
+        let missing = !points[start].1.is_finite();
+        let len = points[start..]
+            .iter()
+            .position(|(_, y)| y.is_finite() == missing)
+            .unwrap_or(points.len() - start);
+        if missing {
+            kept.push(start);
+        } else {
+            // Each run gets its share of the points
+            let share = (len * threshold).div_ceil(finite);
+            kept.extend(
+                lttb_run(&points[start..start + len], share)
+                    .into_iter()
+                    .map(|i| start + i),
+            );
+        }
+        start += len;
+    }
+    kept
+}
+
+/// LTTB over points which all have a value.
+fn lttb_run(points: &[(f64, f64)], threshold: usize) -> Vec<usize> {
+    let n = points.len();
+    if n <= threshold.max(2) {
+        return (0..n).collect();
+    }
+    if threshold < 3 {
+        return vec![0, n - 1];
+    }
+    // The first and last points have buckets of their own
+    let buckets = threshold - 2;
+    let every = (n - 2) as f64 / buckets as f64;
+    let bound = |bucket: usize| {
+        if bucket >= buckets {
+            n - 1
+        } else {
+            (bucket as f64 * every) as usize + 1
+        }
+    };
+    let mut kept = Vec::with_capacity(threshold);
+    kept.push(0);
+    let mut a = 0;
+    for bucket in 0..buckets {
+        // The third vertex is the average of the next bucket
+        let next = &points[bound(bucket + 1)..bound(bucket + 2).max(bound(bucket + 1) + 1)];
+        let (cx, cy) = next.iter().fold((0.0, 0.0), |(x, y), p| (x + p.0, y + p.1));
+        let (cx, cy) = (cx / next.len() as f64, cy / next.len() as f64);
+        let (ax, ay) = points[a];
+        a = (bound(bucket)..bound(bucket + 1))
+            .max_by(|&i, &j| {
+                let area =
+                    |(bx, by): (f64, f64)| ((ax - cx) * (by - ay) - (ax - bx) * (cy - ay)).abs();
+                area(points[i]).total_cmp(&area(points[j]))
+            })
+            .unwrap_or(a);
+        kept.push(a);
+    }
+    kept.push(n - 1);
+    kept
+}
+
+/// Indices of the lowest and highest values in each of `buckets` buckets of
+/// consecutive values, so that no peak is lost.
+pub fn min_max(values: &[f64], buckets: usize) -> Vec<usize> {
+    if values.len() <= 2 * buckets || buckets == 0 {
+        return (0..values.len()).collect();
+    }
+    let mut kept = Vec::with_capacity(2 * buckets);
+    for bucket in 0..buckets {
+        let range = bucket * values.len() / buckets..(bucket + 1) * values.len() / buckets;
+        let finite = range.clone().filter(|&i| values[i].is_finite());
+        let lowest = finite
+            .clone()
+            .min_by(|&i, &j| values[i].total_cmp(&values[j]));
+        let highest = finite.max_by(|&i, &j| values[i].total_cmp(&values[j]));
+        match (lowest, highest) {
+            (Some(lowest), Some(highest)) => {
+                kept.push(lowest.min(highest));
+                if lowest != highest {
+                    kept.push(lowest.max(highest));
+                }
+            }
+            // Only missing values
+            _ => kept.push(range.start),
+        }
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A dry week, but for a downpour at one hour.
+    fn downpour(hour: usize) -> Vec<f64> {
+        (0..168)
+            .map(|i| {
+                if i == hour {
+                    25.0
+                } else {
+                    0.1 * (i % 3) as f64
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lttb_keeps_peaks() {
+        for hour in [1, 50, 101, 166] {
+            let points: Vec<_> = downpour(hour)
+                .into_iter()
+                .enumerate()
+                .map(|(i, p)| (i as f64, p))
+                .collect();
+            let kept = lttb(&points, 20);
+            assert_eq!(kept.len(), 20);
+            assert!(kept.contains(&hour), "lost the downpour at {hour}");
+            assert_eq!((kept[0], kept[19]), (0, 167));
+            assert!(kept.windows(2).all(|w| w[0] < w[1]));
+        }
+    }
+
+    #[test]
+    fn lttb_keeps_gaps() {
+        let mut points: Vec<_> = (0..100).map(|i| (i as f64, (i as f64).sin())).collect();
+        for point in &mut points[40..60] {
+            point.1 = f64::NAN;
+        }
+        let kept = lttb(&points, 10);
+        // Both ends of both runs, and the gap between them
+        for i in [0, 39, 40, 60, 99] {
+            assert!(kept.contains(&i), "{i} not in {kept:?}");
+        }
+        assert!(!kept.contains(&41));
+    }
+
+    #[test]
+    fn min_max_keeps_peaks() {
+        for hour in [0, 50, 167] {
+            let values = downpour(hour);
+            let kept = min_max(&values, 10);
+            assert!(kept.len() <= 20);
+            assert!(kept.contains(&hour), "lost the downpour at {hour}");
+            assert!(kept.windows(2).all(|w| w[0] < w[1]));
+        }
+        // Short series are left alone
+        assert_eq!(min_max(&[1.0, 2.0, 3.0], 10), [0, 1, 2]);
+        assert_eq!(min_max(&[f64::NAN; 6], 2), [0, 3]);
+    }
+}
@@ -0,0 +1,110 @@
+//! Spread of the precipitation forecast, from the members of an ensemble model.
+//!
+//! Each member of the ensemble is a forecast started from slightly different
+//! conditions: the lowest and highest amounts among them tell how uncertain the
+//! precipitation forecast is.
+
+use std::collections::HashMap;
+
+use crate::units::Units;
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize)]
+pub struct PrecipitationSpread {
+    hourly: Members,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize)]
+struct Members {
+    time: Vec<String>,
+    /// Amounts of the control run ("precipitation") and of every member
+    /// ("precipitation_member01", ...), missing for some hours.
+    #[serde(flatten)]
+    amounts: HashMap<String, Vec<Option<f64>>>,
+}
+
+impl PrecipitationSpread {
+    pub fn api_query(latitude: f64, longitude: f64, forecast_days: usize, units: Units) -> String {
+        format!("https://ensemble-api.open-meteo.com/v1/ensemble?latitude={latitude}&longitude={longitude}&hourly=precipitation&models=icon_seamless&forecast_days={forecast_days}&timezone=auto&{}", units.api_parameters())
+    }
+
+    /// Load the spread from open-meteo.com. It only comes in addition to the
+    /// forecast, so failures are logged and leave it out.
+    pub async fn load(
+        latitude: f64,
+        longitude: f64,
+        forecast_days: usize,
+        units: Units,
+    ) -> Option<Self> {
+        let query = Self::api_query(latitude, longitude, forecast_days, units);
+        let spread = match reqwest::get(query).await {
+            Ok(resp) => resp.json::<Self>().await,
+            Err(e) => Err(e),
+        };
+        spread
+            .map_err(|e| log::warn!("failed to load the precipitation spread: {e}"))
+            .ok()
+    }
+
+    /// Lowest and highest amount among the members at each of the local `times`,
+    /// not a number when none is known.
+    pub fn ranges(&self, times: &[String]) -> Vec<(f64, f64)> {
+        let index: HashMap<&str, usize> = self
+            .hourly
+            .time
+            .iter()
+            .enumerate()
+            .map(|(i, time)| (time.as_str(), i))
+            .collect();
+        times
+            .iter()
+            .map(|time| {
+                let Some(&i) = index.get(time.as_str()) else {
+                    return (f64::NAN, f64::NAN);
+                };
+                self.hourly
+                    .amounts
+                    .values()
+                    .filter_map(|amounts| amounts.get(i).copied().flatten())
+                    .fold((f64::NAN, f64::NAN), |(low, high), amount| {
+                        (low.min(amount), high.max(amount))
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_of_members() {
+        let spread: PrecipitationSpread = serde_json::from_str(
+            r#"{
+                "latitude": 48.3,
+                "longitude": 16.3,
+                "hourly_units": {"time": "iso8601", "precipitation": "mm"},
+                "hourly": {
+                    "time": ["2023-11-10T00:00", "2023-11-10T01:00", "2023-11-10T02:00"],
+                    "precipitation": [0.0, 0.4, null],
+                    "precipitation_member01": [0.1, 1.2, null],
+                    "precipitation_member02": [0.0, null, null]
+                }
+            }"#,
+        )
+        .unwrap();
+        let times = [
+            "2023-11-10T01:00",
+            "2023-11-10T00:00",
+            "2023-11-10T02:00",
+            "2023-11-11T00:00",
+        ]
+        .map(String::from);
+        let ranges = spread.ranges(&times);
+        assert_eq!(ranges[..2], [(0.4, 1.2), (0.0, 0.1)]);
+        // Hours no member forecasts, or outside of the ensemble forecast
+        assert!(ranges[2..]
+            .iter()
+            .all(|(low, high)| low.is_nan() && high.is_nan()));
+    }
+}
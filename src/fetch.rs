@@ -0,0 +1,181 @@
+//! Fetching forecasts while the user is still moving the sliders.
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use futures::future::{AbortHandle, Abortable};
+use leptos::{leptos_dom::helpers::TimeoutHandle, *};
+
+use crate::{
+    cache::{self, Cache},
+    data::{Data, LoadError},
+    units::Units,
+};
+
+/// Delay without changes before a new query is sent.
+pub const DEBOUNCE_DELAY: Duration = Duration::from_millis(300);
+
+/// Signal following `source`, updated only once `source` stopped changing for `delay`.
+pub fn debounced<T>(source: impl Fn() -> T + 'static, delay: Duration) -> ReadSignal<T>
+where
+    T: Clone + PartialEq + 'static,
+{
+    let (value, set_value) = create_signal(untrack(&source));
+    create_effect(move |previous: Option<Option<TimeoutHandle>>| {
+        let new_value = source();
+        if let Some(Some(handle)) = previous {
+            handle.clear();
+        }
+        set_timeout_with_handle(
+            move || {
+                if value.get_untracked() != new_value {
+                    set_value.set(new_value);
+                }
+            },
+            delay,
+        )
+        .ok()
+    });
+    value
+}
+
+/// Loads forecasts, aborting the requests still in flight whenever a new query starts.
+///
+/// Dropping an aborted request makes `reqwest` signal its `AbortController`, so the
+/// browser stops downloading it. Aborted loads fail with [`LoadError::aborted`],
+/// which is never shown: resources only keep the result of their latest load.
+///
+/// Cached forecasts are returned right away. When they may be outdated, they are
+/// revalidated in the background and [`Loader::revision`] changes once newer data
+/// is in the cache. If that fails, for example offline, [`Loader::stale_since`] tells
+/// when the forecast on screen was downloaded.
+#[derive(Debug, Clone)]
+pub struct Loader {
+    /// Request of the forecast to show.
+    in_flight: Rc<RefCell<Option<AbortHandle>>>,
+    /// Background revalidation, which must not abort the forecast to show.
+    refreshing: Rc<RefCell<Option<AbortHandle>>>,
+    cache: Cache,
+    revision: RwSignal<usize>,
+    updated_at: RwSignal<Option<f64>>,
+    stale_since: RwSignal<Option<f64>>,
+}
+
+impl Loader {
+    pub fn new() -> Self {
+        Loader {
+            in_flight: Default::default(),
+            refreshing: Default::default(),
+            cache: Cache::load(),
+            revision: create_rw_signal(0),
+            updated_at: create_rw_signal(None),
+            stale_since: create_rw_signal(None),
+        }
+    }
+
+    /// Reactive counter of the background revalidations that succeeded.
+    pub fn revision(&self) -> usize {
+        self.revision.get()
+    }
+
+    /// Reactive download time, in milliseconds since the Unix epoch, of the forecast
+    /// last loaded.
+    pub fn updated_at(&self) -> Option<f64> {
+        self.updated_at.get()
+    }
+
+    /// Reactive download time, in milliseconds since the Unix epoch, of an outdated
+    /// forecast shown because no newer one could be loaded.
+    pub fn stale_since(&self) -> Option<f64> {
+        self.stale_since.get()
+    }
+
+    pub async fn load(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        forecast_days: usize,
+        units: Units,
+    ) -> Result<Data, LoadError> {
+        // Downloading or revalidating the previous query is pointless now, and
+        // must not change the times shown for this one
+        for slot in [&self.in_flight, &self.refreshing] {
+            if let Some(stale) = slot.take() {
+                stale.abort();
+            }
+        }
+        let key = cache::key(latitude, longitude, forecast_days, units);
+        let now = js_sys::Date::now();
+        let cached = self.cache.get(&key, now);
+        match cached {
+            Some(entry) if entry.is_fresh(now) => {
+                self.updated_at.set(Some(entry.fetched_at));
+                self.stale_since.set(None);
+                Ok(entry.data)
+            }
+            Some(entry) => {
+                log::trace!("revalidating cached forecast in the background");
+                self.updated_at.set(Some(entry.fetched_at));
+                self.refresh(latitude, longitude, forecast_days, units);
+                Ok(entry.data)
+            }
+            None => {
+                self.stale_since.set(None);
+                let data = self
+                    .fetch(&self.in_flight, latitude, longitude, forecast_days, units)
+                    .await?;
+                self.updated_at.set(Some(js_sys::Date::now()));
+                Ok(data)
+            }
+        }
+    }
+
+    /// Download the forecast again in the background, even if the cached one is fresh.
+    ///
+    /// On success, [`Loader::revision`] changes so that the new forecast gets loaded.
+    pub fn refresh(&self, latitude: f64, longitude: f64, forecast_days: usize, units: Units) {
+        let loader = self.clone();
+        spawn_local(async move {
+            match loader
+                .fetch(
+                    &loader.refreshing,
+                    latitude,
+                    longitude,
+                    forecast_days,
+                    units,
+                )
+                .await
+            {
+                Ok(_) => loader.revision.update(|revision| *revision += 1),
+                Err(e) if e.is_aborted() => (),
+                Err(e) => {
+                    log::warn!("keeping the forecast on screen: {e}");
+                    loader.stale_since.set(loader.updated_at.get_untracked());
+                }
+            }
+        });
+    }
+
+    /// Download a forecast and store it in the cache, aborting the request in the
+    /// same `slot`.
+    async fn fetch(
+        &self,
+        slot: &RefCell<Option<AbortHandle>>,
+        latitude: f64,
+        longitude: f64,
+        forecast_days: usize,
+        units: Units,
+    ) -> Result<Data, LoadError> {
+        let (handle, registration) = AbortHandle::new_pair();
+        if let Some(stale) = slot.replace(Some(handle)) {
+            log::trace!("aborting stale forecast request");
+            stale.abort();
+        }
+        let request = Data::load(latitude, longitude, forecast_days, units);
+        let data = Abortable::new(request, registration)
+            .await
+            .unwrap_or_else(|_| Err(LoadError::aborted()))?;
+        let key = cache::key(latitude, longitude, forecast_days, units);
+        self.cache.store(key, data.clone(), js_sys::Date::now());
+        Ok(data)
+    }
+}
@@ -0,0 +1,71 @@
+//! Time under the pointer, or selected with the keyboard, shared by all charts.
+//!
+//! Charts draw a crosshair at the hovered time, so that every variable can be read
+//! for the same hour.
+
+use leptos::*;
+use wasm_bindgen::JsCast;
+
+/// Local time of the hovered hour, like "2023-11-10T14:00".
+pub type Hovered = RwSignal<Option<String>>;
+
+/// Position of the pointer, as fractions of the width and height of the element
+/// listening to the event.
+pub fn pointer_position(ev: &web_sys::MouseEvent) -> Option<(f64, f64)> {
+    let element = ev.current_target()?.dyn_into::<web_sys::Element>().ok()?;
+    let rect = element.get_bounding_client_rect();
+    (rect.width() > 0.0 && rect.height() > 0.0).then(|| {
+        (
+            (f64::from(ev.client_x()) - rect.left()) / rect.width(),
+            (f64::from(ev.client_y()) - rect.top()) / rect.height(),
+        )
+    })
+}
+
+/// Index of the item under `x`, for items of `item_width` starting at `start`.
+pub fn index_at(x: f64, start: f64, item_width: f64, len: usize) -> Option<usize> {
+    if len == 0 || x < start {
+        return None;
+    }
+    let index = ((x - start) / item_width).floor() as usize;
+    (index < len).then_some(index)
+}
+
+/// New hovered index after a key press, or `None` if the key is not handled.
+pub fn navigate(key: &str, current: Option<usize>, len: usize) -> Option<Option<usize>> {
+    if len == 0 {
+        return None;
+    }
+    let last = len - 1;
+    match key {
+        "ArrowRight" => Some(Some(current.map_or(0, |i| (i + 1).min(last)))),
+        "ArrowLeft" => Some(Some(current.map_or(last, |i| i.saturating_sub(1)))),
+        "Home" => Some(Some(0)),
+        "End" => Some(Some(last)),
+        "Escape" => Some(None),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_under_pointer() {
+        assert_eq!(index_at(35.0, 30.0, 10.0, 4), Some(0));
+        assert_eq!(index_at(69.9, 30.0, 10.0, 4), Some(3));
+        assert_eq!(index_at(70.0, 30.0, 10.0, 4), None);
+        assert_eq!(index_at(10.0, 30.0, 10.0, 4), None);
+    }
+
+    #[test]
+    fn keyboard_navigation() {
+        assert_eq!(navigate("ArrowRight", None, 3), Some(Some(0)));
+        assert_eq!(navigate("ArrowRight", Some(2), 3), Some(Some(2)));
+        assert_eq!(navigate("ArrowLeft", Some(0), 3), Some(Some(0)));
+        assert_eq!(navigate("End", Some(0), 3), Some(Some(2)));
+        assert_eq!(navigate("Escape", Some(1), 3), Some(None));
+        assert_eq!(navigate("a", Some(1), 3), None);
+    }
+}
@@ -1 +1,2 @@
-
+pub mod downsample;
+pub mod sonify;
@@ -0,0 +1,103 @@
+//! Listening to the forecast: the hours shown, played with WebAudio or saved as
+//! a WAV file, with the same samples (see `weather::sonify`).
+
+use leptos::*;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use weather::sonify::{render, wav, SAMPLE_RATE};
+
+use crate::{table::Row, units::Units, zoom::TimeRange};
+
+/// Samples of the sound of some hours: temperature as pitch, precipitation as noise.
+fn samples(rows: &[Row], units: Units) -> Vec<f32> {
+    let temperatures: Vec<f64> = rows.iter().map(|row| row.temperature).collect();
+    let precipitation: Vec<f64> = rows
+        .iter()
+        .map(|row| units.precipitation.to_mm(row.precipitation))
+        .collect();
+    render(&temperatures, &precipitation)
+}
+
+/// Start playing samples, calling `on_ended` once they are over or stopped.
+fn play(
+    samples: &[f32],
+    on_ended: impl FnOnce() + 'static,
+) -> Result<web_sys::AudioBufferSourceNode, JsValue> {
+    let context = web_sys::AudioContext::new()?;
+    let buffer = context.create_buffer(1, samples.len() as u32, SAMPLE_RATE as f32)?;
+    buffer.copy_to_channel(samples, 0)?;
+    let source = context.create_buffer_source()?;
+    source.set_buffer(Some(&buffer));
+    source.connect_with_audio_node(&context.destination())?;
+    let on_ended = Closure::once_into_js(move || {
+        on_ended();
+        let _ = context.close();
+    });
+    source.set_onended(Some(on_ended.unchecked_ref()));
+    source.start()?;
+    Ok(source)
+}
+
+/// Have the browser save bytes as a file.
+fn download(bytes: &[u8], file_name: &str, mime_type: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(
+        &parts,
+        web_sys::BlobPropertyBag::new().type_(mime_type),
+    )?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+    let link: web_sys::HtmlAnchorElement = document().create_element("a")?.unchecked_into();
+    link.set_href(&url);
+    link.set_download(file_name);
+    link.click();
+    // The download starts after the click is handled, and some browsers still
+    // read the URL a while later
+    set_timeout(
+        move || {
+            let _ = web_sys::Url::revoke_object_url(&url);
+        },
+        std::time::Duration::from_secs(10),
+    );
+    Ok(())
+}
+
+#[component]
+pub fn Listen(
+    rows: Vec<Row>,
+    units: Units,
+    /// Hours shown in the charts, the ones played.
+    visible: RwSignal<TimeRange>,
+) -> impl IntoView {
+    let rows = store_value(rows);
+    let shown_samples = move || {
+        rows.with_value(|rows| samples(&rows[visible.get_untracked().indices(rows.len())], units))
+    };
+    let (playing, set_playing) = create_signal(None::<web_sys::AudioBufferSourceNode>);
+    let on_listen = move |_| {
+        if let Some(source) = playing.get_untracked() {
+            // Ends it, which resets the button
+            let _ = source.stop();
+            return;
+        }
+        match play(&shown_samples(), move || set_playing.set(None)) {
+            Ok(source) => set_playing.set(Some(source)),
+            Err(err) => log::warn!("failed to play the forecast: {err:?}"),
+        }
+    };
+    let on_download = move |_| {
+        if let Err(err) = download(&wav(&shown_samples()), "forecast.wav", "audio/wav") {
+            log::warn!("failed to save the forecast: {err:?}");
+        }
+    };
+
+    view! {
+        <button
+            type="button"
+            aria-pressed=move || playing.with(Option::is_some).to_string()
+            title="The hours shown, in a few seconds: higher tones when warmer, and noise when it rains"
+            on:click=on_listen
+        >
+            {move || if playing.with(Option::is_some) { "Stop ⏹" } else { "Listen 🔊" }}
+        </button>
+        <button type="button" on:click=on_download>"Save as WAV 💾"</button>
+    }
+}
@@ -0,0 +1,260 @@
+use leptos::*;
+use thiserror::Error;
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "weather.locations";
+const LAST_USED_KEY: &str = "weather.last_used";
+
+/// A named location saved by the user.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Location {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// Favourite locations, in the order chosen by the user.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Locations {
+    pub saved: Vec<Location>,
+}
+
+#[derive(Error, Debug, Clone)]
+#[error("Failed to import locations.\n{reason}")]
+pub struct ImportError {
+    reason: String,
+}
+
+impl Locations {
+    /// Load locations from the browser storage, or start empty.
+    pub fn load() -> Self {
+        storage::get(STORAGE_KEY).unwrap_or_default()
+    }
+
+    /// Persist locations in the browser storage.
+    pub fn store(&self) {
+        storage::set(STORAGE_KEY, self);
+    }
+
+    /// Coordinates of the last location looked at, if any.
+    pub fn last_used() -> Option<(f64, f64)> {
+        storage::get(LAST_USED_KEY)
+    }
+
+    /// Remember the location looked at, so that it opens on the next visit.
+    pub fn set_last_used(latitude: f64, longitude: f64) {
+        storage::set(LAST_USED_KEY, &(latitude, longitude));
+    }
+
+    /// Save a location, replacing any previous one with the same name.
+    pub fn save(&mut self, location: Location) {
+        match self.saved.iter_mut().find(|l| l.name == location.name) {
+            Some(previous) => *previous = location,
+            None => self.saved.push(location),
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.saved.len() {
+            self.saved.remove(index);
+        }
+    }
+
+    /// Swap the location at `index` with the one before it.
+    pub fn move_up(&mut self, index: usize) {
+        if index > 0 && index < self.saved.len() {
+            self.saved.swap(index - 1, index);
+        }
+    }
+
+    /// Swap the location at `index` with the one after it.
+    pub fn move_down(&mut self, index: usize) {
+        if index + 1 < self.saved.len() {
+            self.saved.swap(index, index + 1);
+        }
+    }
+
+    /// Saved locations as a JSON array.
+    pub fn export(&self) -> String {
+        serde_json::to_string_pretty(&self.saved).unwrap_or_default()
+    }
+
+    /// Save every location of a JSON array, as produced by [`Locations::export`].
+    ///
+    /// Nothing is saved if any location has invalid coordinates.
+    pub fn import(&mut self, json: &str) -> Result<(), ImportError> {
+        let imported: Vec<Location> = serde_json::from_str(json).map_err(|e| ImportError {
+            reason: format!("the file is not a list of locations.\nCauses:\n\n{}", e),
+        })?;
+        if let Some(invalid) = imported.iter().find(|location| {
+            !(-90.0..=90.0).contains(&location.latitude)
+                || !(-180.0..=180.0).contains(&location.longitude)
+        }) {
+            return Err(ImportError {
+                reason: format!(
+                    "{} has invalid coordinates ({}, {}).",
+                    invalid.name, invalid.latitude, invalid.longitude
+                ),
+            });
+        }
+        for location in imported {
+            self.save(location);
+        }
+        Ok(())
+    }
+}
+
+#[component]
+pub fn Favourites(
+    latitude: ReadSignal<f64>,
+    longitude: ReadSignal<f64>,
+    set_latitude: WriteSignal<f64>,
+    set_longitude: WriteSignal<f64>,
+) -> impl IntoView {
+    let (locations, set_locations) = create_signal(Locations::load());
+    let (name, set_name) = create_signal(String::new());
+    let (import_error, set_import_error) = create_signal(None::<ImportError>);
+
+    create_effect(move |_| Locations::set_last_used(latitude.get(), longitude.get()));
+    create_effect(move |_| locations.with(Locations::store));
+
+    let save = move |_| {
+        let name = name.get().trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let location = Location {
+            name,
+            latitude: latitude.get(),
+            longitude: longitude.get(),
+        };
+        set_locations.update(|locations| locations.save(location));
+        set_name.set(String::new());
+    };
+    let import = move |ev: ev::Event| {
+        let input = event_target::<web_sys::HtmlInputElement>(&ev);
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        spawn_local(async move {
+            let text = wasm_bindgen_futures::JsFuture::from(file.text()).await;
+            let result = match text.ok().and_then(|text| text.as_string()) {
+                Some(json) => {
+                    let mut updated = locations.get_untracked();
+                    updated.import(&json).map(|_| set_locations.set(updated))
+                }
+                None => Err(ImportError {
+                    reason: "the file could not be read.".to_string(),
+                }),
+            };
+            set_import_error.set(result.err());
+        });
+    };
+    let export_href = move || {
+        let json = locations.with(Locations::export);
+        let encoded = js_sys::encode_uri_component(&json);
+        format!("data:application/json;charset=utf-8,{}", encoded)
+    };
+
+    view! {
+        <fieldset>
+            <legend>"Favourites ⭐"</legend>
+            <ul>
+                {move || locations.with(|locations| locations.saved
+                    .iter()
+                    .cloned()
+                    .enumerate()
+                    .map(|(i, location)| {
+                        let label = location.name.clone();
+                        view! {
+                            <li>
+                                <button type="button" on:click=move |_| {
+                                    log::trace!("selecting location {}", location.name);
+                                    batch(|| {
+                                        set_latitude.set(location.latitude);
+                                        set_longitude.set(location.longitude);
+                                    });
+                                }>
+                                    {label}
+                                </button>
+                                <button type="button" title="Move up" on:click=move |_| set_locations.update(|l| l.move_up(i))>"↑"</button>
+                                <button type="button" title="Move down" on:click=move |_| set_locations.update(|l| l.move_down(i))>"↓"</button>
+                                <button type="button" title="Delete" on:click=move |_| set_locations.update(|l| l.remove(i))>"✕"</button>
+                            </li>
+                        }
+                    })
+                    .collect_view()
+                )}
+            </ul>
+            <input type="text"
+                placeholder="Name this location"
+                on:input=move |ev| set_name.set(event_target_value(&ev))
+                prop:value=name
+            />
+            <button type="button" on:click=save>"Save"</button>
+            <a href=export_href download="locations.json">"Export"</a>
+            <label>
+                "Import: "
+                <input type="file" accept="application/json" on:change=import/>
+            </label>
+            {move || import_error.get().map(|e| view! { <p class="error">{e.to_string()}</p> })}
+        </fieldset>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(name: &str) -> Location {
+        Location {
+            name: name.to_string(),
+            latitude: 48.3,
+            longitude: 16.3,
+        }
+    }
+
+    #[test]
+    fn reorder_and_remove() {
+        let mut locations = Locations::default();
+        for name in ["Office", "Home", "Cabin"] {
+            locations.save(location(name));
+        }
+        locations.move_up(2);
+        locations.move_down(0);
+        locations.remove(5);
+        let names: Vec<_> = locations.saved.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["Cabin", "Office", "Home"]);
+        locations.remove(1);
+        assert_eq!(locations.saved.len(), 2);
+    }
+
+    #[test]
+    fn export_import_round_trip() -> Result<(), ImportError> {
+        let mut locations = Locations::default();
+        locations.save(location("Office"));
+        locations.save(location("Home"));
+        let mut imported = Locations::default();
+        imported.save(location("Home"));
+        imported.import(&locations.export())?;
+        let names: Vec<_> = imported.saved.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["Home", "Office"]);
+        assert!(imported.import("not json").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_coordinates_are_rejected() {
+        let mut locations = Locations::default();
+        for (latitude, longitude) in [(91.0, 0.0), (-90.5, 0.0), (0.0, 180.5), (0.0, -181.0)] {
+            let json = format!(
+                r#"[{{"name": "Home", "latitude": 1, "longitude": 1}}, {{"name": "Away", "latitude": {latitude}, "longitude": {longitude}}}]"#
+            );
+            assert!(locations.import(&json).is_err());
+        }
+        assert!(locations.saved.is_empty());
+        let edges = r#"[{"name": "Pole", "latitude": -90, "longitude": 180}]"#;
+        assert!(locations.import(edges).is_ok());
+    }
+}
@@ -1,57 +1,142 @@
 use leptos::*;
 
+mod cache;
 mod data;
 use data::Data;
+mod describe;
+mod ensemble;
+mod fetch;
+use fetch::Loader;
+mod hover;
+mod listen;
+mod locations;
+mod meteogram;
+mod moon;
+mod nowcast;
+use locations::{Favourites, Locations};
+mod offline;
+use offline::StaleBanner;
+mod paint;
+mod permalink;
+use permalink::Permalink;
+mod planner;
+mod refresh;
+use refresh::Refresh;
+mod series;
+mod storage;
+mod sun;
+mod table;
+mod theme;
+use theme::{ColorScale, ThemeChoice, ThemeSelector};
+mod tween;
+mod units;
+use units::{Units, UnitsSelector};
+mod weather_code;
+mod zoom;
 
 fn main() -> anyhow::Result<()> {
     std::panic::set_hook(Box::new(console_error_panic_hook::hook));
     console_log::init_with_level(log::Level::Trace)
         .map_err(|_| anyhow::anyhow!("failed to initialize logger."))?;
     leptos::mount_to_body(|| view! { <App/> });
+    offline::register_service_worker();
     Ok(())
 }
 
 const DEFAULT_LATITUDE: f64 = 51.5;
 const DEFAULT_LONGITUDE: f64 = -0.1;
+const DEFAULT_FORECAST_DAYS: usize = 2;
 
 #[component]
 fn App() -> impl IntoView {
-    let (latitude, set_latitude) = create_signal(DEFAULT_LATITUDE);
-    let (longitude, set_longitude) = create_signal(DEFAULT_LONGITUDE);
-    let (forecast_days, set_forecast_days) = create_signal(2);
+    // A shared link takes precedence over the last location looked at
+    let permalink = Permalink::current();
+    let (last_latitude, last_longitude) =
+        Locations::last_used().unwrap_or((DEFAULT_LATITUDE, DEFAULT_LONGITUDE));
+    let (latitude, set_latitude) = create_signal(permalink.latitude.unwrap_or(last_latitude));
+    let (longitude, set_longitude) = create_signal(permalink.longitude.unwrap_or(last_longitude));
+    let (forecast_days, set_forecast_days) =
+        create_signal(permalink.forecast_days.unwrap_or(DEFAULT_FORECAST_DAYS));
+    let (units, set_units) = create_signal(permalink.units.unwrap_or_else(Units::load));
+    let (theme, set_theme) = create_signal(ThemeChoice::load());
+    let (color_scale, set_color_scale) = create_signal(ColorScale::load());
+    theme::provide_theme(theme, color_scale);
+    tween::provide_previous_rows();
+    permalink::sync_with_url(
+        (latitude, set_latitude),
+        (longitude, set_longitude),
+        (forecast_days, set_forecast_days),
+        (units, set_units),
+    );
 
+    // Sliders change the query many times per second: wait until they settle
+    let query = fetch::debounced(
+        move || (latitude.get(), longitude.get(), forecast_days.get(), units.get()),
+        fetch::DEBOUNCE_DELAY,
+    );
+    let loader = Loader::new();
+    let stale_since = {
+        let loader = loader.clone();
+        Signal::derive(move || loader.stale_since())
+    };
+    let updated_at = {
+        let loader = loader.clone();
+        Signal::derive(move || loader.updated_at())
+    };
+    let refresh = {
+        let loader = loader.clone();
+        Callback::new(move |()| {
+            let (latitude, longitude, forecast_days, units) = query.get_untracked();
+            loader.refresh(latitude, longitude, forecast_days, units);
+        })
+    };
+    let (pending, set_pending) = create_signal(false);
     let data = create_resource(
-        move || (latitude.get(), longitude.get(), forecast_days.get()),
-        move |(latitude, longitude, forecast_days)| async move {
-            Data::load(latitude, longitude, forecast_days).await
+        {
+            let loader = loader.clone();
+            move || (query.get(), loader.revision())
+        },
+        move |((latitude, longitude, forecast_days, units), _)| {
+            let loader = loader.clone();
+            async move { loader.load(latitude, longitude, forecast_days, units).await }
         },
     );
 
     view! {
-        // Present the data
-        {move || match data.get() {
-            None => view! { <p>"Loading..."</p> }.into_view(),
-            Some(data) => view! {
-                <ErrorBoundary
-                    // the fallback receives a signal containing current errors
-                    fallback=|errors| view! {
-                        <div class="error">
-                            <p>"Your query has problems! Errors: "</p>
-                            // we can render a list of errors as strings, if we'd like
-                            <ul>
-                                {move || errors.get()
-                                    .into_iter()
-                                    .map(|(_, e)| view! { <li>{e.to_string()}</li>})
-                                    .collect_view()
-                                }
-                            </ul>
-                        </div>
-                    }
-                >
-                    <div>{data}</div    >
-                </ErrorBoundary>
-            }.into_view(),
-        }}
+        <StaleBanner stale_since/>
+        <Refresh updated_at on_refresh=refresh/>
+        // Present the data, keeping the previous forecast on screen while a new one loads
+        <Transition
+            fallback=|| view! { <p>"Loading..."</p> }
+            set_pending
+        >
+            <div class="forecast" class:pending=pending>
+                {move || pending.get().then(|| view! {
+                    <progress class="loading_indicator" aria-label="Loading new forecast"/>
+                })}
+                // Aborted loads were replaced by newer ones, which are shown instead
+                {move || data.get().filter(|data| !matches!(data, Err(e) if e.is_aborted())).map(|data| view! {
+                    <ErrorBoundary
+                        // the fallback receives a signal containing current errors
+                        fallback=|errors| view! {
+                            <div class="error">
+                                <p>"Your query has problems! Errors: "</p>
+                                // we can render a list of errors as strings, if we'd like
+                                <ul>
+                                    {move || errors.get()
+                                        .into_iter()
+                                        .map(|(_, e)| view! { <li>{e.to_string()}</li>})
+                                        .collect_view()
+                                    }
+                                </ul>
+                            </div>
+                        }
+                    >
+                        <div>{data}</div>
+                    </ErrorBoundary>
+                })}
+            </div>
+        </Transition>
         <form on:submit=move |_| {data.refetch();}>
             <fieldset>
                 <legend>"Forecast days: " {move || forecast_days.get()}</legend>
@@ -59,7 +144,7 @@ fn App() -> impl IntoView {
                     min="1"
                     max="16"
                     on:input = move |ev| {
-                        let v = event_target_value(&ev).parse().unwrap_or(2);
+                        let v = event_target_value(&ev).parse().unwrap_or(DEFAULT_FORECAST_DAYS);
                         log::trace!("setting forcast days to {v}");
                         // event_target_value is a Leptos helper function
                         // it functions the same way as event.target.value
@@ -114,13 +199,21 @@ fn App() -> impl IntoView {
                     prop:value=longitude
                 />
             </fieldset>
+            <UnitsSelector units set_units/>
+            <ThemeSelector choice=theme set_choice=set_theme scale=color_scale set_scale=set_color_scale/>
+            <Favourites
+                latitude=latitude
+                longitude=longitude
+                set_latitude=set_latitude
+                set_longitude=set_longitude
+            />
         </form>
         <details>
             <summary>
                 API call
             </summary>
-            https://api.open-meteo.com/v1/forecast?latitude=48.3&longitude=16.3&hourly=apparent_temperature,precipitation_probability,precipitation,wind_speed_10m,wind_direction_10m&forecast_days=2&daily=uv_index_max
-            {let url = Data::api_query(latitude.get(), longitude.get(), forecast_days.get());
+            https://api.open-meteo.com/v1/forecast?latitude=48.3&longitude=16.3&hourly=apparent_temperature,precipitation_probability,precipitation,wind_speed_10m,wind_direction_10m,weather_code,is_day,temperature_2m,uv_index&forecast_days=2&daily=uv_index_max,weather_code,temperature_2m_max,temperature_2m_min,apparent_temperature_max,apparent_temperature_min,precipitation_sum,precipitation_probability_max,precipitation_hours,wind_speed_10m_max,wind_direction_10m_dominant&current=temperature_2m,apparent_temperature,relative_humidity_2m,precipitation,weather_code,wind_speed_10m,wind_direction_10m,wind_gusts_10m,cloud_cover,pressure_msl,is_day&minutely_15=precipitation,rain,snowfall,lightning_potential&forecast_minutely_15=12&timezone=auto
+            {let url = Data::api_query(latitude.get(), longitude.get(), forecast_days.get(), units.get());
             	view!{ <a href={url.clone()}>{url}</a> }
             }
         </details>
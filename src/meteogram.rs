@@ -0,0 +1,1129 @@
+//! Hourly forecast on a single chart, after yr.no's meteogram (see `yr_visualization.svg`).
+//!
+//! Lanes share one time axis, from top to bottom: weather symbols, temperature and
+//! precipitation (with their own y-axes, left and right), wind, and the UV index.
+//! The precipitation bars stand in a band from the lowest to the highest amount of
+//! an ensemble forecast, once it is loaded.
+//!
+//! The visible part of the time axis can be zoomed with the mouse wheel, by
+//! pinching or by brushing over the time axis (or with Shift held), and panned by
+//! dragging. When a new forecast replaces the one shown, the series move from their
+//! previous values to the new ones.
+
+use leptos::*;
+use wasm_bindgen::JsCast;
+use weather::downsample::{lttb, min_max};
+
+use crate::{
+    data::{weekday, wind_arrow},
+    describe::{self, HiddenTable, ENJOY_UV, SEEK_UV},
+    hover::{index_at, navigate, pointer_position, Hovered},
+    paint::{Anchor, Backend, CanvasPainter, Paint, Painter, SvgPainter},
+    series::{area_path, band_path, line_path, smooth_path, Marker},
+    sun::SunTrack,
+    table::{display, Row},
+    theme::{scale_color, use_palette, Palette},
+    tween,
+    units::Units,
+    zoom::{tick_step, TimeRange},
+};
+
+/// Width of the plot, in user units of the SVG, whatever the time range shown.
+const PLOT_WIDTH: f64 = 480.0;
+const MARGIN_LEFT: f64 = 30.0;
+const MARGIN_RIGHT: f64 = 30.0;
+const SYMBOLS_HEIGHT: f64 = 24.0;
+const PLOT_HEIGHT: f64 = 120.0;
+const WIND_HEIGHT: f64 = 22.0;
+const UV_HEIGHT: f64 = 16.0;
+const AXIS_HEIGHT: f64 = 18.0;
+/// Hours that can separate two weather symbols or wind arrows.
+const SYMBOL_STEPS: [usize; 6] = [1, 2, 3, 6, 12, 24];
+/// Smallest precipitation range shown on the right axis, in mm.
+const MIN_PRECIPITATION_MM: f64 = 4.0;
+/// Top of the UV index lane, above which bars are cut.
+const MAX_UV: f64 = 11.0;
+/// Zoom factor of one wheel notch or key press.
+const ZOOM_STEP: f64 = 0.8;
+/// Values drawn for each hour: temperature, precipitation amount, probability and
+/// range, wind, and UV index.
+const SERIES: usize = 6;
+
+/// Position of every lane and mapping from values to SVG coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    visible: TimeRange,
+    hour_width: f64,
+    /// Bottom and top of the temperature axis, in the unit shown.
+    temperature_range: (f64, f64),
+    temperature_step: f64,
+    /// Top of the precipitation axis, in the unit shown.
+    precipitation_max: f64,
+}
+
+impl Layout {
+    /// Layout showing the `visible` hours, with y-axes fitting the values shown,
+    /// including the precipitation `spread` of each hour, if known.
+    pub fn new(rows: &[Row], spread: &[(f64, f64)], units: Units, visible: TimeRange) -> Self {
+        let indices = visible.indices(rows.len());
+        let shown = &rows[indices.clone()];
+        let (low, high) = shown
+            .iter()
+            .map(|row| row.temperature)
+            .filter(|t| t.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), t| {
+                (low.min(t), high.max(t))
+            });
+        let (low, high) = if low <= high { (low, high) } else { (0.0, 0.0) };
+        // At least ten degrees, in round steps
+        let step = if high - low > 40.0 { 10.0 } else { 5.0 };
+        let mut bottom = (low / step).floor() * step;
+        let mut top = (high / step).ceil() * step;
+        while top - bottom < 2.0 * step {
+            top += step;
+            bottom -= step;
+        }
+        let highest = spread
+            .get(indices)
+            .unwrap_or_default()
+            .iter()
+            .map(|(_, high)| *high);
+        let precipitation_max = shown
+            .iter()
+            .map(|row| row.precipitation)
+            .chain(highest)
+            .filter(|p| p.is_finite())
+            .fold(
+                units.precipitation.convert_mm(MIN_PRECIPITATION_MM),
+                f64::max,
+            );
+        Layout {
+            visible,
+            hour_width: PLOT_WIDTH / visible.len(),
+            temperature_range: (bottom, top),
+            temperature_step: step,
+            precipitation_max,
+        }
+    }
+
+    pub fn width(&self) -> f64 {
+        MARGIN_LEFT + PLOT_WIDTH + MARGIN_RIGHT
+    }
+
+    pub fn height(&self) -> f64 {
+        SYMBOLS_HEIGHT + PLOT_HEIGHT + WIND_HEIGHT + UV_HEIGHT + AXIS_HEIGHT
+    }
+
+    /// The viewBox, moved along the time axis to the visible hours.
+    fn view_box(&self) -> String {
+        format!(
+            "{} 0 {} {}",
+            self.left() - MARGIN_LEFT,
+            self.width(),
+            self.height()
+        )
+    }
+
+    /// Horizontal position of a time, in hours since the first one.
+    pub fn x(&self, hour: f64) -> f64 {
+        MARGIN_LEFT + hour * self.hour_width
+    }
+
+    /// Time at a fraction of the width of the SVG, in hours since the first one.
+    pub fn hour_at(&self, fraction: f64) -> f64 {
+        self.visible.at(self.plot_fraction(fraction))
+    }
+
+    /// Fraction of the plot at a fraction of the width of the SVG.
+    fn plot_fraction(&self, fraction: f64) -> f64 {
+        (fraction * self.width() - MARGIN_LEFT) / PLOT_WIDTH
+    }
+
+    fn left(&self) -> f64 {
+        self.x(self.visible.start)
+    }
+
+    fn right(&self) -> f64 {
+        self.x(self.visible.end)
+    }
+
+    fn plot_top(&self) -> f64 {
+        SYMBOLS_HEIGHT
+    }
+
+    fn plot_bottom(&self) -> f64 {
+        SYMBOLS_HEIGHT + PLOT_HEIGHT
+    }
+
+    fn wind_top(&self) -> f64 {
+        self.plot_bottom()
+    }
+
+    fn uv_top(&self) -> f64 {
+        self.wind_top() + WIND_HEIGHT
+    }
+
+    fn axis_top(&self) -> f64 {
+        self.uv_top() + UV_HEIGHT
+    }
+
+    pub fn temperature_y(&self, temperature: f64) -> f64 {
+        let (bottom, top) = self.temperature_range;
+        self.plot_bottom() - (temperature - bottom) / (top - bottom) * PLOT_HEIGHT
+    }
+
+    pub fn precipitation_y(&self, precipitation: f64) -> f64 {
+        self.plot_bottom()
+            - precipitation.clamp(0.0, self.precipitation_max) / self.precipitation_max
+                * PLOT_HEIGHT
+    }
+
+    /// Precipitation probability, in %, over the whole height of the plot.
+    fn probability_y(&self, probability: f64) -> f64 {
+        self.plot_bottom() - probability.clamp(0.0, 100.0) / 100.0 * PLOT_HEIGHT
+    }
+
+    /// UV index, from the bottom to the top of its lane, leaving room for markers.
+    fn uv_y(&self, uv: f64) -> f64 {
+        self.axis_top() - 1.0 - uv.clamp(0.0, MAX_UV) / MAX_UV * (UV_HEIGHT - 4.0)
+    }
+
+    /// Elevation of the sun, in degrees, from the horizon to the zenith over the plot.
+    fn sun_y(&self, elevation: f64) -> f64 {
+        self.plot_bottom() - elevation.clamp(0.0, 90.0) / 90.0 * PLOT_HEIGHT
+    }
+
+    /// Values labelled on the temperature axis.
+    pub fn temperature_ticks(&self) -> Vec<f64> {
+        let (bottom, top) = self.temperature_range;
+        let steps = ((top - bottom) / self.temperature_step).round() as usize;
+        (0..=steps)
+            .map(|i| bottom + i as f64 * self.temperature_step)
+            .collect()
+    }
+
+    /// Values labelled on the precipitation axis.
+    fn precipitation_ticks(&self) -> [f64; 3] {
+        [0.0, self.precipitation_max / 2.0, self.precipitation_max]
+    }
+
+    /// Hours between two weather symbols, so that they do not overlap.
+    fn symbol_step(&self) -> usize {
+        SYMBOL_STEPS
+            .into_iter()
+            .find(|&step| step as f64 * self.hour_width >= SYMBOLS_HEIGHT)
+            .unwrap_or(24)
+    }
+}
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{value}")
+    } else {
+        format!("{value:.1}")
+    }
+}
+
+#[component]
+pub fn Meteogram(
+    rows: Vec<Row>,
+    units: Units,
+    hovered: Hovered,
+    /// Hours shown, shared with the other charts.
+    visible: RwSignal<TimeRange>,
+    /// Elevation of the sun, for the night shading.
+    sun: SunTrack,
+    /// Whether to also draw the elevation of the sun.
+    #[prop(into)]
+    show_sun: Signal<bool>,
+    /// Lowest and highest precipitation of an ensemble forecast at each hour, empty
+    /// until it is loaded.
+    #[prop(into)]
+    precipitation_spread: Signal<Vec<(f64, f64)>>,
+) -> impl IntoView {
+    if rows.is_empty() {
+        return ().into_view();
+    }
+    let hours = rows.len();
+    let bounds = TimeRange::all(hours);
+    // Values drawn, moving from those of the previous forecast
+    let drawn_rows = tween::from_previous(&rows);
+    let stored_rows = store_value(rows);
+    let sun = store_value(sun);
+    let palette = use_palette();
+    let layout = create_memo(move |_| {
+        drawn_rows.with(|rows| {
+            precipitation_spread.with(|spread| Layout::new(rows, spread, units, visible.get()))
+        })
+    });
+    let hovered_index = create_memo(move |_| {
+        hovered.with(|time| {
+            let time = time.as_ref()?;
+            stored_rows.with_value(|rows| rows.iter().position(|row| &row.time == time))
+        })
+    });
+    let hover = move |index: Option<usize>| {
+        hovered
+            .set(index.and_then(|i| stored_rows.with_value(|rows| Some(rows.get(i)?.time.clone()))))
+    };
+    // Hours told by the summary and the table, for screen readers
+    let shown = create_memo(move |_| visible.get().indices(hours));
+    let summary =
+        move || stored_rows.with_value(|rows| describe::meteogram(&rows[shown.get()], units));
+    let table = move || {
+        stored_rows.with_value(|rows| {
+            let shown = shown.get();
+            let spread = precipitation_spread.get();
+            let rows = rows[shown.clone()]
+                .iter()
+                .zip(shown)
+                .map(|(row, i)| {
+                    let (day, hour) = row.time.split_once('T').unwrap_or((&row.time, ""));
+                    vec![
+                        format!("{} {hour}", weekday(day).unwrap_or(day)),
+                        row.weather_code.description(),
+                        format!("{} {}", display(row.temperature), units.temperature.symbol()),
+                        format!(
+                            "{} {}",
+                            display(row.precipitation),
+                            units.precipitation.symbol()
+                        ),
+                        format!("{}%", display(row.precipitation_probability)),
+                        spread
+                            .get(i)
+                            .map(|range| range_text(*range, units))
+                            .unwrap_or_default(),
+                        format!(
+                            "{} {} from the {}",
+                            display(row.wind_speed),
+                            units.wind_speed.symbol(),
+                            describe::compass(row.wind_direction)
+                        ),
+                        format!(
+                            "{}, {}",
+                            display(row.uv_index),
+                            describe::uv_advice(row.uv_index)
+                        ),
+                    ]
+                })
+                .collect();
+            view! {
+                <HiddenTable
+                    caption="Values of the meteogram".to_string()
+                    headers=vec!["Time", "Weather", "Temperature", "Precipitation", "Probability", "Ensemble range", "Wind", "UV index"]
+                    rows
+                />
+            }
+        })
+    };
+    // Too many points make the SVG slow to update, draw them on a canvas instead
+    let backend =
+        create_memo(move |_| Backend::for_points(visible.get().len().ceil() as usize * SERIES));
+    let canvas = create_node_ref::<html::Canvas>();
+    let plot = create_node_ref::<html::Div>();
+    let resized = create_trigger();
+    let on_resize = window_event_listener(ev::resize, move |_| resized.notify());
+    on_cleanup(move || on_resize.remove());
+    // Pixels across the plot, to draw no more points than that
+    let pixels = create_rw_signal(PLOT_WIDTH as usize);
+    create_effect(move |_| {
+        resized.track();
+        if let Some(plot) = plot.get() {
+            let width = f64::from(plot.client_width()) * window().device_pixel_ratio();
+            if width > 0.0 {
+                let plot_width = width * PLOT_WIDTH / layout.get_untracked().width();
+                pixels.set(plot_width.round() as usize);
+            }
+        }
+    });
+    let with_lanes = move |paint: &mut dyn FnMut(Lanes)| {
+        drawn_rows.with(|rows| {
+            sun.with_value(|sun| {
+                precipitation_spread.with(|spread| {
+                    paint(Lanes {
+                        rows,
+                        spread,
+                        layout: layout.get(),
+                        pixels: pixels.get(),
+                        units,
+                        sun,
+                        show_sun: show_sun.get(),
+                        palette: palette.get(),
+                    })
+                })
+            })
+        })
+    };
+    create_effect(move |_| {
+        resized.track();
+        if let (Backend::Canvas, Some(canvas)) = (backend.get(), canvas.get()) {
+            with_lanes(&mut |lanes| draw_canvas(&canvas, lanes));
+        }
+    });
+    // Pressed pointers: identifier, hour grabbed and current fraction of the plot
+    let pointers = store_value(Vec::<(i32, f64, f64)>::new());
+    // Hours selected by brushing, from where it started to the pointer
+    let (brush, set_brush) = create_signal(None::<(f64, f64)>);
+
+    let on_pointer_down = move |ev: ev::PointerEvent| {
+        let Some((x, y)) = pointer_position(&ev) else {
+            return;
+        };
+        let layout = layout.get_untracked();
+        let hour = layout.hour_at(x);
+        if let Some(element) = ev
+            .current_target()
+            .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+        {
+            let _ = element.set_pointer_capture(ev.pointer_id());
+        }
+        if ev.shift_key() || y * layout.height() > layout.axis_top() {
+            set_brush.set(Some((hour, hour)));
+        } else {
+            pointers.update_value(|pointers| {
+                pointers.push((ev.pointer_id(), hour, layout.plot_fraction(x)))
+            });
+            hover(index_at(hour, 0.0, 1.0, hours));
+        }
+    };
+    let on_pointer_move = move |ev: ev::PointerEvent| {
+        let Some((x, _)) = pointer_position(&ev) else {
+            return;
+        };
+        let layout = layout.get_untracked();
+        let hour = layout.hour_at(x);
+        if let Some((start, _)) = brush.get_untracked() {
+            set_brush.set(Some((start, hour)));
+            return;
+        }
+        let anchors = pointers.with_value(|pointers| {
+            pointers
+                .iter()
+                .map(|&(id, grabbed, fraction)| {
+                    if id == ev.pointer_id() {
+                        (grabbed, layout.plot_fraction(x))
+                    } else {
+                        (grabbed, fraction)
+                    }
+                })
+                .collect::<Vec<_>>()
+        });
+        if anchors.is_empty() {
+            hover(index_at(hour, 0.0, 1.0, hours));
+            return;
+        }
+        pointers.update_value(|pointers| {
+            for pointer in pointers
+                .iter_mut()
+                .filter(|pointer| pointer.0 == ev.pointer_id())
+            {
+                pointer.2 = layout.plot_fraction(x);
+            }
+        });
+        visible.update(|visible| *visible = visible.anchored(&anchors, bounds));
+    };
+    let on_pointer_up = move |ev: ev::PointerEvent| {
+        pointers.update_value(|pointers| pointers.retain(|pointer| pointer.0 != ev.pointer_id()));
+        if let Some((start, end)) = brush.get_untracked() {
+            set_brush.set(None);
+            // A click on the time axis is not a selection
+            if (end - start).abs() >= 1.0 {
+                visible.set(TimeRange::new(start.min(end), start.max(end)).clamp(bounds));
+            }
+        }
+    };
+    let on_wheel = move |ev: ev::WheelEvent| {
+        let Some((x, _)) = pointer_position(&ev) else {
+            return;
+        };
+        ev.prevent_default();
+        let center = layout.get_untracked().hour_at(x);
+        let factor = if ev.delta_y() > 0.0 {
+            1.0 / ZOOM_STEP
+        } else {
+            ZOOM_STEP
+        };
+        visible.update(|visible| *visible = visible.zoom(factor, center, bounds));
+    };
+    let on_key_down = move |ev: ev::KeyboardEvent| {
+        let range = visible.get_untracked();
+        match ev.key().as_str() {
+            "+" | "=" => visible.set(range.zoom(ZOOM_STEP, range.at(0.5), bounds)),
+            "-" => visible.set(range.zoom(1.0 / ZOOM_STEP, range.at(0.5), bounds)),
+            "0" => visible.set(bounds),
+            key => {
+                let Some(index) = navigate(key, hovered_index.get_untracked(), hours) else {
+                    return;
+                };
+                // Follow the hovered hour
+                if let Some(i) = index {
+                    let hour = i as f64 + 0.5;
+                    if hour < range.start || hour > range.end {
+                        visible.set(range.anchored(&[(hour, 0.5)], bounds));
+                    }
+                }
+                hover(index);
+            }
+        }
+        ev.prevent_default();
+    };
+
+    view! {
+        <h2 id="meteogram_title">{"Meteogram 📊"}</h2>
+        <p id="meteogram_summary" class="chart_summary">{summary}</p>
+        <p id="meteogram_help" class="visually_hidden">
+            "Use the arrow keys to read the values of each hour, plus and minus to zoom, and 0 to show every hour."
+        </p>
+        <div class="meteogram">
+            <div class="meteogram_plot" node_ref=plot>
+                {move || (backend.get() == Backend::Canvas).then(|| view! {
+                    <canvas class="meteogram_canvas" node_ref=canvas aria-hidden="true"/>
+                })}
+                <svg
+                    viewBox=move || layout.get().view_box()
+                    xmlns="http://www.w3.org/2000/svg"
+                    width="100%"
+                    font-size="7px"
+                    fill=move || palette.get().text
+                    tabindex="0"
+                    role="application"
+                    aria-roledescription="chart"
+                    aria-labelledby="meteogram_title"
+                    aria-describedby="meteogram_summary meteogram_help"
+                    on:pointerdown=on_pointer_down
+                    on:pointermove=on_pointer_move
+                    on:pointerup=on_pointer_up
+                    on:pointercancel=on_pointer_up
+                    on:pointerleave=move |_| {
+                        if pointers.with_value(Vec::is_empty) {
+                            hovered.set(None);
+                        }
+                    }
+                    on:wheel=on_wheel
+                    on:keydown=on_key_down
+                >
+                    {move || (backend.get() == Backend::Svg).then(|| {
+                        let mut painter = SvgPainter::new();
+                        with_lanes(&mut |lanes| paint_lanes(&mut painter, lanes));
+                        painter
+                    })}
+                    {move || hovered_index.get().map(|i| {
+                        let x = layout.get().x(i as f64 + 0.5);
+                        view! {
+                            <line class="crosshair" x1=x x2=x y1="0" y2=layout.get().axis_top() stroke=palette.get().text stroke-width="0.8" pointer-events="none"/>
+                        }
+                    })}
+                    {move || brush.get().map(|(start, end)| {
+                        let layout = layout.get();
+                        let (start, end) = (layout.x(start.min(end)), layout.x(start.max(end)));
+                        view! {
+                            <rect class="brush" x=start y="0" width={end - start} height=layout.height() fill=palette.get().rain opacity="0.2" pointer-events="none"/>
+                        }
+                    })}
+                </svg>
+                {move || hovered_index.get().and_then(|i| stored_rows.with_value(|rows| Some((i, rows.get(i)?.clone())))).map(|(i, row)| {
+                    let layout = layout.get_untracked();
+                    let spread = precipitation_spread.with(|spread| spread.get(i).copied());
+                    let percent = ((layout.x(i as f64 + 0.5) - layout.left() + MARGIN_LEFT) / layout.width() * 100.0).clamp(0.0, 100.0);
+                    view! {
+                        <div
+                            class="chart_tooltip"
+                            aria-hidden="true"
+                            style={format!("left: {percent}%; transform: translateX(-{percent}%)")}
+                        >
+                            <HourSummary row spread units/>
+                        </div>
+                    }
+                })}
+            </div>
+            // Read out the hour reached with the keyboard, in place of the tooltip
+            <p class="visually_hidden" aria-live="polite">
+                {move || hovered_index.get().and_then(|i| stored_rows.with_value(|rows| Some(describe::hour(rows.get(i)?, units))))}
+            </p>
+            {move || (visible.get() != bounds).then(|| view! {
+                <button type="button" class="reset_zoom" on:click=move |_| visible.set(bounds)>"Reset zoom 🔍"</button>
+            })}
+        </div>
+        {table}
+    }
+    .into_view()
+}
+
+/// Everything drawn in the lanes of the meteogram, by either backend.
+#[derive(Clone, Copy)]
+struct Lanes<'a> {
+    rows: &'a [Row],
+    /// Lowest and highest precipitation of the ensemble at each hour, if loaded.
+    spread: &'a [(f64, f64)],
+    layout: Layout,
+    /// Pixels across the plot, to draw no more points than that.
+    pixels: usize,
+    units: Units,
+    sun: &'a SunTrack,
+    show_sun: bool,
+    palette: Palette,
+}
+
+/// Draw the content of the meteogram.
+fn paint_lanes<P: Painter>(painter: &mut P, lanes: Lanes) {
+    let Lanes {
+        rows,
+        spread,
+        layout,
+        pixels,
+        units,
+        sun,
+        show_sun,
+        palette,
+    } = lanes;
+    // Where the temperature line turns from warm to cold
+    let freezing_y = layout
+        .temperature_y(units.temperature.convert_celsius(0.0))
+        .clamp(layout.plot_top(), layout.plot_bottom());
+    // Hours drawn: the visible ones, and their neighbours for lines to reach the edges
+    let shown = layout.visible.indices(rows.len());
+    let drawn = shown.start.saturating_sub(1)..(shown.end + 1).min(rows.len());
+    // Lines with no more points than pixels across the plot
+    let downsample = |points: Vec<(f64, f64)>| {
+        lttb(&points, pixels)
+            .into_iter()
+            .map(|i| points[i])
+            .collect::<Vec<_>>()
+    };
+    let points = |y: &dyn Fn(&Row) -> f64| {
+        downsample(
+            rows[drawn.clone()]
+                .iter()
+                .zip(drawn.clone())
+                .map(|(row, i)| (layout.x(i as f64 + 0.5), y(row)))
+                .collect(),
+        )
+    };
+    let (left, right) = (layout.left(), layout.right());
+    let bar_width = (layout.hour_width * 0.8).max(0.5);
+    let ticks = tick_step(layout.visible.len());
+    let symbols = layout.symbol_step();
+
+    // Along the scale of temperatures, with a sharp change at freezing
+    let [warm, mild, cool, cold] =
+        [1.0, 2.0 / 3.0, 1.0 / 3.0, 0.0].map(|t| scale_color(palette.temperature, t));
+    painter.gradient(
+        "meteogram_temperature",
+        &[
+            (layout.plot_top(), &warm),
+            (freezing_y, &mild),
+            (freezing_y, &cool),
+            (layout.plot_bottom(), &cold),
+        ],
+        1.0,
+    );
+    painter.gradient(
+        "meteogram_probability",
+        &[
+            (layout.plot_top(), palette.rain),
+            (layout.plot_bottom(), "transparent"),
+        ],
+        0.3,
+    );
+    painter.gradient(
+        "meteogram_spread",
+        &[
+            (layout.plot_top(), palette.rain),
+            (layout.plot_bottom(), palette.rain),
+        ],
+        0.35,
+    );
+    painter.clipped(
+        "meteogram_plot_area",
+        (left, 0.0, PLOT_WIDTH, layout.height()),
+        |painter| {
+            // Night shading, behind everything else
+            for (start, end) in sun.nights() {
+                if end > layout.visible.start && start < layout.visible.end {
+                    painter.rect(
+                        layout.x(start),
+                        layout.plot_top(),
+                        (end - start) * layout.hour_width,
+                        layout.axis_top() - layout.plot_top(),
+                        Paint::Color(palette.night),
+                    );
+                }
+            }
+            // Elevation of the sun, down to where it sets and from where it rises
+            if show_sun {
+                let elevations: Vec<_> = sun
+                    .points()
+                    .filter(|(hour, _)| {
+                        (layout.visible.start - 1.0..=layout.visible.end + 1.0).contains(hour)
+                    })
+                    .collect();
+                let up = |i: Option<usize>| {
+                    i.and_then(|i| elevations.get(i))
+                        .is_some_and(|(_, elevation)| *elevation >= 0.0)
+                };
+                let points = elevations
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &(hour, elevation))| {
+                        let shown = up(Some(i)) || up(i.checked_sub(1)) || up(Some(i + 1));
+                        let y = if shown {
+                            layout.sun_y(elevation)
+                        } else {
+                            f64::NAN
+                        };
+                        (layout.x(hour), y)
+                    })
+                    .collect();
+                painter.path(
+                    &line_path(&downsample(points)),
+                    Paint::None,
+                    Paint::Color(palette.sun),
+                    0.8,
+                );
+            }
+            // Grid of the temperature axis
+            for t in layout.temperature_ticks() {
+                let y = layout.temperature_y(t);
+                painter.line((left, y), (right, y), Paint::Color(palette.grid), 0.5);
+            }
+            // Days and hours, fewer of them when zoomed out
+            for (row, i) in rows[shown.clone()].iter().zip(shown.clone()) {
+                let Some((day, hour)) = row.time.split_once('T') else {
+                    continue;
+                };
+                let x = layout.x(i as f64);
+                let midnight = hour.starts_with("00");
+                if midnight {
+                    painter.line(
+                        (x, 0.0),
+                        (x, layout.axis_top()),
+                        Paint::Color(palette.grid),
+                        0.8,
+                    );
+                }
+                if let Some(hour) = hour.get(..2) {
+                    if ticks <= 24 && hour.parse::<usize>().is_ok_and(|hour| hour % ticks == 0) {
+                        painter.text(x, layout.axis_top() + 8.0, hour, Anchor::Middle, 7.0, false);
+                    }
+                }
+                if midnight || i == shown.start {
+                    let label = format!(
+                        "{} {}",
+                        weekday(day)
+                            .map(|weekday| &weekday[..3])
+                            .unwrap_or_default(),
+                        day.get(8..10).unwrap_or_default()
+                    );
+                    painter.text(
+                        x.max(left) + 2.0,
+                        layout.axis_top() + 16.0,
+                        &label,
+                        Anchor::Start,
+                        7.0,
+                        true,
+                    );
+                }
+            }
+            // Precipitation probability, as an area behind the amounts, with a
+            // dotted outline to not be told by its shade alone
+            let probability = points(&|row| layout.probability_y(row.precipitation_probability));
+            painter.path(
+                &area_path(&probability, layout.plot_bottom()),
+                Paint::Gradient("meteogram_probability"),
+                Paint::None,
+                0.0,
+            );
+            painter.dashed(&[1.0, 2.0], |painter| {
+                painter.path(
+                    &line_path(&probability),
+                    Paint::None,
+                    Paint::Color(palette.rain),
+                    0.6,
+                )
+            });
+            // Range of the ensemble, as a band behind the bars
+            let band: Vec<_> = spread
+                .get(drawn.clone())
+                .unwrap_or_default()
+                .iter()
+                .zip(drawn.clone())
+                .map(|(&(low, high), i)| {
+                    let (top, bottom) = (layout.precipitation_y(high), layout.precipitation_y(low));
+                    (layout.x(i as f64 + 0.5), top, bottom)
+                })
+                .collect();
+            painter.path(
+                &band_path(&band),
+                Paint::Gradient("meteogram_spread"),
+                Paint::None,
+                0.0,
+            );
+            // Precipitation bars. When there are more hours than pixels, only the
+            // driest and wettest of them are drawn.
+            let amounts: Vec<f64> = rows[shown.clone()]
+                .iter()
+                .map(|row| row.precipitation)
+                .collect();
+            for i in min_max(&amounts, pixels / 2) {
+                let (row, i) = (&rows[shown.start + i], shown.start + i);
+                if row.precipitation > 0.0 {
+                    let y = layout.precipitation_y(row.precipitation);
+                    painter.rect(
+                        layout.x(i as f64 + 0.5) - bar_width / 2.0,
+                        y,
+                        bar_width,
+                        layout.plot_bottom() - y,
+                        Paint::Color(palette.rain),
+                    );
+                }
+            }
+            // Temperature line, in warm colours above freezing, and in cold ones
+            // and dashed below
+            let temperature = smooth_path(&points(&|row| layout.temperature_y(row.temperature)));
+            let draw_temperature = |painter: &mut P| {
+                painter.path(
+                    &temperature,
+                    Paint::None,
+                    Paint::Gradient("meteogram_temperature"),
+                    1.5,
+                )
+            };
+            painter.clipped(
+                "meteogram_above_freezing",
+                (left, 0.0, PLOT_WIDTH, freezing_y),
+                draw_temperature,
+            );
+            painter.clipped(
+                "meteogram_below_freezing",
+                (left, freezing_y, PLOT_WIDTH, layout.height() - freezing_y),
+                |painter| painter.dashed(&[3.0, 2.0], draw_temperature),
+            );
+            // Weather symbols and wind, every few hours
+            for (row, i) in rows[drawn.clone()].iter().zip(drawn.clone()) {
+                if i % symbols != 0 {
+                    continue;
+                }
+                let center = layout.x(i as f64 + symbols as f64 / 2.0);
+                painter.weather_icon(
+                    row.weather_code,
+                    row.is_day,
+                    center - SYMBOLS_HEIGHT / 2.0 + 2.0,
+                    0.0,
+                    SYMBOLS_HEIGHT - 4.0,
+                );
+                if row.wind_direction.is_finite() {
+                    painter.path(
+                        &wind_arrow_path(center, layout.wind_top() + 7.0, row.wind_direction),
+                        Paint::None,
+                        Paint::Color(palette.text),
+                        1.0,
+                    );
+                }
+                if row.wind_speed.is_finite() {
+                    painter.text(
+                        center,
+                        layout.wind_top() + 19.0,
+                        &format_value(row.wind_speed),
+                        Anchor::Middle,
+                        6.0,
+                        false,
+                    );
+                }
+            }
+            // UV index, as bars in the colours of its scale, with dashed lines at
+            // the limits of the advice
+            painter.dashed(&[2.0, 2.0], |painter| {
+                for uv in [ENJOY_UV, SEEK_UV] {
+                    let y = layout.uv_y(uv);
+                    painter.line((left, y), (right, y), Paint::Color(palette.grid), 0.5);
+                }
+            });
+            let uv_color = |uv: f64| scale_color(palette.uv, uv / MAX_UV);
+            let indices: Vec<f64> = rows[shown.clone()].iter().map(|row| row.uv_index).collect();
+            for i in min_max(&indices, pixels / 2) {
+                let (row, i) = (&rows[shown.start + i], shown.start + i);
+                if row.uv_index > 0.0 {
+                    let y = layout.uv_y(row.uv_index);
+                    painter.rect(
+                        layout.x(i as f64 + 0.5) - bar_width / 2.0,
+                        y,
+                        bar_width,
+                        layout.uv_y(0.0) - y,
+                        Paint::Color(&uv_color(row.uv_index)),
+                    );
+                }
+            }
+            // The peak of each day, marked by the shape of its advice, so that it is
+            // not told by colour alone
+            for (row, i) in day_peaks(&rows[drawn.clone()]) {
+                let i = drawn.start + i;
+                painter.path(
+                    &uv_marker(row.uv_index).path(
+                        layout.x(i as f64 + 0.5),
+                        layout.uv_y(row.uv_index),
+                        4.0,
+                    ),
+                    Paint::Color(&uv_color(row.uv_index)),
+                    Paint::Color(palette.text),
+                    0.4,
+                );
+            }
+        },
+    );
+    // Temperature axis, on the left
+    for t in layout.temperature_ticks() {
+        painter.text(
+            left - 3.0,
+            layout.temperature_y(t) + 2.5,
+            &format!("{t}°"),
+            Anchor::End,
+            7.0,
+            false,
+        );
+    }
+    let axis_left = left - MARGIN_LEFT + 2.0;
+    painter.text(
+        axis_left,
+        layout.plot_top() - 4.0,
+        units.temperature.symbol(),
+        Anchor::Start,
+        7.0,
+        false,
+    );
+    // Precipitation axis, on the right
+    for p in layout.precipitation_ticks() {
+        painter.text(
+            right + 3.0,
+            layout.precipitation_y(p) + 2.5,
+            &format_value(p),
+            Anchor::Start,
+            7.0,
+            false,
+        );
+    }
+    painter.text(
+        right + 3.0,
+        layout.plot_top() - 4.0,
+        units.precipitation.symbol(),
+        Anchor::Start,
+        7.0,
+        false,
+    );
+    painter.text(
+        axis_left,
+        layout.wind_top() + 19.0,
+        units.wind_speed.symbol(),
+        Anchor::Start,
+        6.0,
+        false,
+    );
+    painter.text(
+        axis_left,
+        layout.axis_top() - 2.0,
+        "UV",
+        Anchor::Start,
+        6.0,
+        false,
+    );
+}
+
+/// Hours with the highest UV index of each day, with their index in `rows`.
+fn day_peaks(rows: &[Row]) -> Vec<(&Row, usize)> {
+    let mut peaks: Vec<(&Row, usize)> = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        if !row.uv_index.is_finite() {
+            continue;
+        }
+        match peaks.last_mut() {
+            Some(peak) if peak.0.day() == row.day() => {
+                // The first of the highest
+                if row.uv_index > peak.0.uv_index {
+                    *peak = (row, i);
+                }
+            }
+            _ => peaks.push((row, i)),
+        }
+    }
+    peaks
+}
+
+/// Shape of the advice under a UV index.
+fn uv_marker(uv: f64) -> Marker {
+    if uv < ENJOY_UV {
+        Marker::Circle
+    } else if uv < SEEK_UV {
+        Marker::Triangle
+    } else {
+        Marker::Diamond
+    }
+}
+
+/// Range of the ensemble, like "0–1.2 mm".
+fn range_text((low, high): (f64, f64), units: Units) -> String {
+    if low.is_finite() && high.is_finite() {
+        format!(
+            "{}–{} {}",
+            display(low),
+            display(high),
+            units.precipitation.symbol()
+        )
+    } else {
+        "–".to_string()
+    }
+}
+
+/// Draw the lanes on a canvas under the SVG, at the resolution of the screen.
+fn draw_canvas(canvas: &web_sys::HtmlCanvasElement, lanes: Lanes) {
+    let layout = lanes.layout;
+    let ratio = window().device_pixel_ratio();
+    let width = (f64::from(canvas.client_width()) * ratio).round();
+    let height = (f64::from(canvas.client_height()) * ratio).round();
+    // Resizing also clears the canvas
+    canvas.set_width(width as u32);
+    canvas.set_height(height as u32);
+    let Some(context) = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .and_then(|context| context.dyn_into::<web_sys::CanvasRenderingContext2d>().ok())
+    else {
+        log::warn!("no 2d context for the meteogram canvas");
+        return;
+    };
+    // Same coordinates as the viewBox of the SVG
+    let (scale_x, scale_y) = (width / layout.width(), height / layout.height());
+    let x = -(layout.left() - MARGIN_LEFT) * scale_x;
+    if context
+        .set_transform(scale_x, 0.0, 0.0, scale_y, x, 0.0)
+        .is_err()
+    {
+        return;
+    }
+    let mut painter = CanvasPainter::new(context, lanes.palette);
+    paint_lanes(&mut painter, lanes);
+}
+
+/// Arrow 10 units long centered on (x, y), pointing where the wind blows to.
+fn wind_arrow_path(x: f64, y: f64, direction: f64) -> String {
+    let (sin, cos) = direction.to_radians().sin_cos();
+    // Rotated clockwise, like an SVG rotation, from an arrow pointing down
+    let point =
+        |dx: f64, dy: f64| format!("{} {}", x + dx * cos - dy * sin, y + dx * sin + dy * cos);
+    format!(
+        "M{}L{}M{}L{}L{}",
+        point(0.0, -5.0),
+        point(0.0, 5.0),
+        point(-3.0, 2.0),
+        point(0.0, 5.0),
+        point(3.0, 2.0)
+    )
+}
+
+/// Every value of one hour, for the tooltip.
+#[component]
+fn HourSummary(row: Row, spread: Option<(f64, f64)>, units: Units) -> impl IntoView {
+    let (day, hour) = row.time.split_once('T').unwrap_or((&row.time, ""));
+    let temperature_unit = units.temperature.symbol();
+
+    view! {
+        <strong>{weekday(day).unwrap_or_default()} " " {hour.to_string()}</strong>
+        <p>{row.weather_code.description()}</p>
+        <dl>
+            <dt>"Temperature"</dt>
+            <dd>{display(row.temperature)} {temperature_unit} " (feels like " {display(row.apparent_temperature)} {temperature_unit} ")"</dd>
+            <dt>"Precipitation"</dt>
+            <dd>{display(row.precipitation)} " " {units.precipitation.symbol()} " (" {display(row.precipitation_probability)} "%)"</dd>
+            {spread.map(|range| view! {
+                <dt>"Ensemble range"</dt>
+                <dd>{range_text(range, units)}</dd>
+            })}
+            <dt>"Wind"</dt>
+            <dd>{wind_arrow(row.wind_direction)} " " {display(row.wind_speed)} " " {units.wind_speed.symbol()}</dd>
+            <dt>"UV"</dt>
+            <dd>{display(row.uv_index)} " (" {describe::uv_advice(row.uv_index)} ")"</dd>
+        </dl>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather_code::WeatherCode;
+
+    fn rows(temperatures: &[f64]) -> Vec<Row> {
+        temperatures
+            .iter()
+            .enumerate()
+            .map(|(i, &temperature)| Row {
+                time: format!("2023-11-10T{i:02}:00"),
+                weather_code: WeatherCode::ClearSky,
+                is_day: (7..17).contains(&i),
+                temperature,
+                apparent_temperature: temperature,
+                precipitation: 0.0,
+                precipitation_probability: 0.0,
+                wind_speed: 0.0,
+                wind_direction: 0.0,
+                uv_index: 0.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn temperature_axis() {
+        let layout = Layout::new(
+            &rows(&[-2.3, 4.0, 11.5]),
+            &[],
+            Units::METRIC,
+            TimeRange::all(3),
+        );
+        assert_eq!(layout.temperature_ticks(), [-5.0, 0.0, 5.0, 10.0, 15.0]);
+        assert_eq!(layout.temperature_y(-5.0), layout.plot_bottom());
+        assert_eq!(layout.temperature_y(15.0), layout.plot_top());
+        // Constant temperatures still get a readable range
+        let layout = Layout::new(&rows(&[3.0, 3.0]), &[], Units::METRIC, TimeRange::all(2));
+        assert_eq!(layout.temperature_ticks(), [-5.0, 0.0, 5.0, 10.0]);
+    }
+
+    #[test]
+    fn precipitation_axis() {
+        let layout = Layout::new(&rows(&[0.0]), &[], Units::METRIC, TimeRange::all(1));
+        assert_eq!(layout.precipitation_y(0.0), layout.plot_bottom());
+        assert_eq!(
+            layout.precipitation_y(MIN_PRECIPITATION_MM),
+            layout.plot_top()
+        );
+        assert_eq!(layout.precipitation_y(100.0), layout.plot_top());
+        // The axis reaches up to the wettest member of the ensemble
+        let layout = Layout::new(
+            &rows(&[0.0]),
+            &[(0.0, 10.0)],
+            Units::METRIC,
+            TimeRange::all(1),
+        );
+        assert_eq!(layout.precipitation_ticks(), [0.0, 5.0, 10.0]);
+    }
+
+    #[test]
+    fn uv_peaks() {
+        let mut rows = rows(&[0.0; 4]);
+        for (row, uv) in rows.iter_mut().zip([1.0, 6.0, 6.0, 2.0]) {
+            row.uv_index = uv;
+        }
+        rows[3].time = "2023-11-11T00:00".to_string();
+        let peaks: Vec<_> = day_peaks(&rows).into_iter().map(|(_, i)| i).collect();
+        assert_eq!(peaks, [1, 3]);
+        assert_eq!(uv_marker(6.0), Marker::Triangle);
+    }
+
+    #[test]
+    fn zoomed_layout() {
+        let rows = rows(&[0.0, 0.0, 0.0, 20.0]);
+        let layout = Layout::new(&rows, &[], Units::METRIC, TimeRange::new(1.0, 3.0));
+        // The hidden 20° do not stretch the axis
+        assert_eq!(layout.temperature_ticks(), [-5.0, 0.0, 5.0]);
+        assert_eq!(layout.left(), MARGIN_LEFT + PLOT_WIDTH / 2.0);
+        assert_eq!(layout.hour_at(MARGIN_LEFT / layout.width()), 1.0);
+        assert_eq!(layout.symbol_step(), 1);
+    }
+}
@@ -0,0 +1,217 @@
+//! Position and phase of the moon, computed locally for any place and date.
+//!
+//! The position is the low precision one of the Astronomical Almanac, good to
+//! about 0.3°, so moonrise and moonset are within a few minutes.
+
+use crate::sun::{self, crossings, midnight};
+
+/// Elevation of the centre of the moon at moonrise and moonset, in degrees,
+/// seen from the surface and accounting for its radius and for refraction.
+pub const MOONRISE_ELEVATION: f64 = -0.833;
+
+/// Ecliptic longitude and latitude, and horizontal parallax of the moon, in
+/// degrees, `days` after J2000.0.
+pub fn ecliptic(days: f64) -> (f64, f64, f64) {
+    let centuries = days / 36525.0;
+    let term = |a: f64, b: f64| (a + b * centuries).to_radians();
+    let longitude = 218.32 + 481_267.881 * centuries + 6.29 * term(134.9, 477_198.85).sin()
+        - 1.27 * term(259.2, -413_335.38).sin()
+        + 0.66 * term(235.7, 890_534.23).sin()
+        + 0.21 * term(269.9, 954_397.70).sin()
+        - 0.19 * term(357.5, 35_999.05).sin()
+        - 0.11 * term(186.6, 966_404.05).sin();
+    let latitude = 5.13 * term(93.3, 483_202.03).sin() + 0.28 * term(228.2, 960_400.87).sin()
+        - 0.28 * term(318.3, 6_003.18).sin()
+        - 0.17 * term(217.6, -407_332.20).sin();
+    let parallax = 0.9508
+        + 0.0518 * term(134.9, 477_198.85).cos()
+        + 0.0095 * term(259.2, -413_335.38).cos()
+        + 0.0078 * term(235.7, 890_534.23).cos()
+        + 0.0028 * term(269.9, 954_397.70).cos();
+    (longitude, latitude, parallax)
+}
+
+/// Declination and right ascension of the moon, in radians, `days` after J2000.0.
+pub fn equatorial(days: f64) -> (f64, f64) {
+    let (longitude, latitude, _) = ecliptic(days);
+    let (longitude, latitude) = (longitude.to_radians(), latitude.to_radians());
+    let obliquity = sun::obliquity(days);
+    let declination = (latitude.sin() * obliquity.cos()
+        + latitude.cos() * obliquity.sin() * longitude.sin())
+    .asin();
+    let right_ascension = (longitude.sin() * obliquity.cos() - latitude.tan() * obliquity.sin())
+        .atan2(longitude.cos());
+    (declination, right_ascension)
+}
+
+/// Azimuth, clockwise from north, and elevation of the moon, in degrees, seen
+/// from the surface of the Earth, `days` after J2000.0.
+pub fn position(latitude: f64, longitude: f64, days: f64) -> (f64, f64) {
+    let (azimuth, elevation) = sun::horizontal(latitude, longitude, days, equatorial(days));
+    // The moon is close enough for it to look lower than from the centre of the Earth
+    let parallax = ecliptic(days).2;
+    (azimuth, elevation - parallax * elevation.to_radians().cos())
+}
+
+/// Phase of the moon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Phase {
+    /// Fraction of the lunation since new moon, from 0 to 1.
+    pub age: f64,
+    /// Fraction of the disc lit by the sun, from 0 to 1.
+    pub illumination: f64,
+}
+
+impl Phase {
+    /// Phase `days` after J2000.0.
+    pub fn at(days: f64) -> Self {
+        let (longitude, latitude, _) = ecliptic(days);
+        let elongation = longitude - sun::ecliptic_longitude(days);
+        let cos_elongation = latitude.to_radians().cos() * elongation.to_radians().cos();
+        Phase {
+            age: elongation.rem_euclid(360.0) / 360.0,
+            illumination: (1.0 - cos_elongation) / 2.0,
+        }
+    }
+
+    /// Index of the nearest of the eight named phases, from new moon.
+    fn octant(&self) -> usize {
+        (self.age * 8.0).round() as usize % 8
+    }
+
+    pub fn name(&self) -> &'static str {
+        [
+            "New moon",
+            "Waxing crescent",
+            "First quarter",
+            "Waxing gibbous",
+            "Full moon",
+            "Waning gibbous",
+            "Last quarter",
+            "Waning crescent",
+        ][self.octant()]
+    }
+
+    pub fn emoji(&self) -> &'static str {
+        ["🌑", "🌒", "🌓", "🌔", "🌕", "🌖", "🌗", "🌘"][self.octant()]
+    }
+}
+
+/// Events of the moon during a day, in hours since local midnight.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoonDay {
+    /// Moonrise, if the moon rises that day, which it does not about once a month.
+    pub moonrise: Option<f64>,
+    pub moonset: Option<f64>,
+    /// Phase at local noon.
+    pub phase: Phase,
+}
+
+impl MoonDay {
+    /// Events of a date like "2023-11-10", at a place at `utc_offset_seconds` from UTC.
+    pub fn new(latitude: f64, longitude: f64, date: &str, utc_offset_seconds: f64) -> Option<Self> {
+        let start = midnight(date, utc_offset_seconds)?;
+        let events = crossings(
+            |days| position(latitude, longitude, days).1,
+            MOONRISE_ELEVATION,
+            start,
+            24.0,
+        );
+        let first = |rising: bool| {
+            events
+                .iter()
+                .find(|event| event.1 == rising)
+                .map(|event| event.0)
+        };
+        Some(MoonDay {
+            moonrise: first(true),
+            moonset: first(false),
+            phase: Phase::at(start + 0.5),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sun::{days_since_j2000, format_hour, SunDay};
+
+    fn phase(utc: &str) -> Phase {
+        Phase::at(days_since_j2000(utc, 0.0).unwrap())
+    }
+
+    #[test]
+    fn phases() {
+        // New moon of the total solar eclipse, 2024-04-08T18:21 UTC
+        let new = phase("2024-04-08T18:21");
+        assert!(new.illumination < 0.005, "{new:?}");
+        assert_eq!(new.name(), "New moon");
+        // First quarter, 2024-01-18T03:52 UTC
+        let first_quarter = phase("2024-01-18T03:52");
+        assert!(
+            (first_quarter.illumination - 0.5).abs() < 0.02,
+            "{first_quarter:?}"
+        );
+        assert!((first_quarter.age - 0.25).abs() < 0.01, "{first_quarter:?}");
+        assert_eq!(first_quarter.emoji(), "🌓");
+        // Full moon of the partial lunar eclipse, 2024-09-18T02:34 UTC
+        let full = phase("2024-09-18T02:34");
+        assert!(full.illumination > 0.995, "{full:?}");
+        assert_eq!(full.name(), "Full moon");
+    }
+
+    #[test]
+    fn rise_and_set() {
+        // Vienna, the day before the full moon of 2024-09-18: it rises about when the sun sets
+        let moon = MoonDay::new(48.21, 16.37, "2024-09-17", 7200.0).unwrap();
+        let sun = SunDay::new(48.21, 16.37, "2024-09-17", 7200.0).unwrap();
+        let (moonrise, sunset) = (moon.moonrise.unwrap(), sun.sunset.unwrap());
+        assert!((moonrise - sunset).abs() < 1.0, "{moonrise} {sunset}");
+        // The moon is on the horizon when it rises
+        let start = midnight("2024-09-17", 7200.0).unwrap();
+        let elevation = position(48.21, 16.37, start + moonrise / 24.0).1;
+        assert!((elevation - MOONRISE_ELEVATION).abs() < 0.01, "{elevation}");
+    }
+
+    /// Within two minutes of `expected`, like "18:56", which is rounded to the minute.
+    fn assert_near(hours: Option<f64>, expected: &str) {
+        let hours = hours.expect(expected);
+        let (hour, minute) = expected.split_once(':').unwrap();
+        let expected = hour.parse::<f64>().unwrap() + minute.parse::<f64>().unwrap() / 60.0;
+        assert!(
+            (hours - expected).abs() <= 2.0 / 60.0,
+            "{} instead of {}",
+            format_hour(hours),
+            format_hour(expected)
+        );
+    }
+
+    #[test]
+    fn almanac() {
+        // Example 47.a of Meeus, Astronomical Algorithms: the moon on 1992-04-12 at
+        // 0h TD, within the 0.3° of the low precision position
+        let days = 2_448_724.5 - 2_451_545.0;
+        let (longitude, latitude, parallax) = ecliptic(days);
+        assert!(
+            (longitude.rem_euclid(360.0) - 133.162655).abs() < 0.3,
+            "{longitude}"
+        );
+        assert!((latitude - -3.229126).abs() < 0.3, "{latitude}");
+        assert!((parallax - 0.991990).abs() < 0.01, "{parallax}");
+        let (declination, right_ascension) = equatorial(days);
+        assert!((declination.to_degrees() - 13.768368).abs() < 0.3);
+        assert!((right_ascension.to_degrees() - 134.688470).abs() < 0.3);
+        // Moonrise and moonset of the full lunar theory of the same chapter (its
+        // main terms, with nutation and ΔT = 69 s), with the upper limb on the
+        // horizon and 34' of refraction, like the US Naval Observatory
+        let vienna = MoonDay::new(48.21, 16.37, "2024-09-17", 7200.0).unwrap();
+        assert_near(vienna.moonset, "05:17");
+        assert_near(vienna.moonrise, "18:56");
+        let washington = MoonDay::new(38.8895, -77.0353, "2024-06-20", -14400.0).unwrap();
+        assert_near(washington.moonset, "04:03");
+        assert_near(washington.moonrise, "19:50");
+        let sydney = MoonDay::new(-33.87, 151.21, "2024-01-01", 39600.0).unwrap();
+        assert_near(sydney.moonset, "10:14");
+        assert_near(sydney.moonrise, "23:39");
+    }
+}
@@ -0,0 +1,327 @@
+//! Precipitation in the next two hours, from 15-minutely data.
+
+use leptos::*;
+
+use crate::{describe::HiddenTable, theme::use_palette, units::PrecipitationUnit};
+
+/// Number of 15-minute steps shown.
+pub const STEPS: usize = 8;
+const STEP_MINUTES: usize = 15;
+/// Below this, in mm per 15 minutes, a step is considered dry.
+const WET_THRESHOLD_MM: f64 = 0.05;
+/// Top of the chart, in mm per 15 minutes: heavy rain.
+const MAX_PRECIPITATION_MM: f64 = 2.5;
+
+/// Precipitation every 15 minutes. Values are sums over the preceding 15 minutes.
+///
+/// Steps missing their precipitation have no data, rather than being taken as dry.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct Minutely15 {
+    time: Vec<String>,
+    precipitation: Vec<Option<f64>>,
+    #[serde(default)]
+    rain: Vec<Option<f64>>,
+    #[serde(default)]
+    snowfall: Vec<Option<f64>>,
+    /// Only available where the underlying model provides it, in J/kg.
+    #[serde(default)]
+    lightning_potential: Vec<Option<f64>>,
+}
+
+/// One 15-minute step of the nowcast.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Step {
+    /// Not known when the data is missing.
+    pub precipitation_mm: Option<f64>,
+    pub snow: bool,
+    pub lightning: bool,
+}
+
+impl Step {
+    fn is_wet(&self) -> bool {
+        self.precipitation_mm
+            .is_some_and(|mm| mm >= WET_THRESHOLD_MM)
+    }
+}
+
+impl Minutely15 {
+    /// Steps of the next two hours, starting at the 15-minute step containing `now`.
+    ///
+    /// `now` is a local time as returned by the API, like "2023-11-10T14:15": such
+    /// strings sort chronologically.
+    pub fn next_steps(&self, now: &str, unit: PrecipitationUnit) -> Vec<(String, Step)> {
+        let start = self
+            .time
+            .iter()
+            .position(|time| time.as_str() > now)
+            .unwrap_or(self.time.len());
+        (start..self.time.len().min(start + STEPS))
+            .map(|i| {
+                let value = |values: &[Option<f64>]| values.get(i).copied().flatten();
+                let step = Step {
+                    precipitation_mm: value(&self.precipitation).map(|amount| unit.to_mm(amount)),
+                    snow: value(&self.snowfall).unwrap_or(0.0) > 0.0
+                        && value(&self.rain).unwrap_or(0.0) == 0.0,
+                    lightning: self
+                        .lightning_potential
+                        .get(i)
+                        .copied()
+                        .flatten()
+                        .is_some_and(|potential| potential > 0.0),
+                };
+                (self.time[i].clone(), step)
+            })
+            .collect()
+    }
+}
+
+/// A sentence like "Rain starting in ~30 min, lasting ~45 min".
+///
+/// Only the steps before the first one without data are told about.
+pub fn summary(steps: &[Step]) -> String {
+    let known = steps
+        .iter()
+        .take_while(|step| step.precipitation_mm.is_some())
+        .count();
+    if known == 0 {
+        return "No precipitation data for the next two hours.".to_string();
+    }
+    if known == steps.len() {
+        return known_summary(steps, "the next two hours");
+    }
+    let horizon = format!("the next ~{} min", known * STEP_MINUTES);
+    format!(
+        "{} No data after that.",
+        known_summary(&steps[..known], &horizon)
+    )
+}
+
+/// Summary of steps which all have data, up to `horizon`, like "the next two hours".
+fn known_summary(steps: &[Step], horizon: &str) -> String {
+    let Some(first_wet) = steps.iter().position(Step::is_wet) else {
+        return format!("No precipitation in {horizon}.");
+    };
+    let wet_steps = steps[first_wet..]
+        .iter()
+        .take_while(|step| step.is_wet())
+        .count();
+    let kind = if steps[first_wet..first_wet + wet_steps]
+        .iter()
+        .all(|step| step.snow)
+    {
+        "Snow"
+    } else {
+        "Rain"
+    };
+    let until_end = first_wet + wet_steps == steps.len();
+    match (first_wet, until_end) {
+        (0, true) => format!("{kind} for {horizon}."),
+        (0, false) => format!("{kind} stopping in ~{} min.", wet_steps * STEP_MINUTES),
+        (_, true) => format!("{kind} starting in ~{} min.", first_wet * STEP_MINUTES),
+        (_, false) => format!(
+            "{kind} starting in ~{} min, lasting ~{} min.",
+            first_wet * STEP_MINUTES,
+            wet_steps * STEP_MINUTES
+        ),
+    }
+}
+
+#[component]
+pub fn Nowcast(
+    minutely_15: Minutely15,
+    /// Local time of the current conditions.
+    now: String,
+    unit: PrecipitationUnit,
+) -> impl IntoView {
+    let steps = minutely_15.next_steps(&now, unit);
+    if steps.is_empty() {
+        return ().into_view();
+    }
+    let sentence = summary(&steps.iter().map(|(_, step)| *step).collect::<Vec<_>>());
+    let precipitation_to_height =
+        |mm: f64| mm.min(MAX_PRECIPITATION_MM) / MAX_PRECIPITATION_MM * 10.0;
+    let palette = use_palette();
+    let color = move || palette.get().rain;
+    let table_rows = steps
+        .iter()
+        .map(|(time, step)| {
+            let hour = time.split_once('T').map_or(time.as_str(), |(_, hour)| hour);
+            let kind = if !step.is_wet() {
+                "dry"
+            } else if step.snow {
+                "snow"
+            } else {
+                "rain"
+            };
+            let precipitation = match step.precipitation_mm {
+                Some(mm) => format!("{:.2} {}, {kind}", unit.convert_mm(mm), unit.symbol()),
+                None => "no data".to_string(),
+            };
+            vec![
+                hour.to_string(),
+                precipitation,
+                if step.lightning { "yes" } else { "no" }.to_string(),
+            ]
+        })
+        .collect();
+
+    view! {
+        <div class="svg_graph nowcast">
+            <h2 id="nowcast_title">{"Next two hours ☔"}</h2>
+            <p id="nowcast_summary">{sentence}</p>
+            <svg
+                viewBox={ format!("0 0 {} 14", STEPS * 2) }
+                xmlns="http://www.w3.org/2000/svg"
+                width="100%"
+                role="img"
+                aria-labelledby="nowcast_title"
+                aria-describedby="nowcast_summary"
+            >
+                <line x1="0" x2={STEPS * 2} y1="10" y2="10" stroke=color stroke-width="0.05"/>
+                {steps
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (time, step))| {
+                        let hour = time.split_once('T').map_or(time.clone(), |(_, hour)| hour.to_string());
+                        let bar = match step.precipitation_mm {
+                            Some(mm) => {
+                                let height = precipitation_to_height(mm);
+                                let amount = unit.convert_mm(mm);
+                                view! {
+                                    <rect
+                                        x={i as f64 * 2.0 + 0.2}
+                                        y={10.0 - height}
+                                        width="1.6"
+                                        height=height
+                                        fill=color
+                                        opacity={if step.snow { 0.5 } else { 1.0 }}
+                                    >
+                                        <title>{hour.clone()} ": " {format!("{amount:.2}")} " " {unit.symbol()}</title>
+                                    </rect>
+                                }
+                            }
+                            // Unavailable, not dry: an empty outline over the whole height
+                            None => view! {
+                                <rect
+                                    x={i as f64 * 2.0 + 0.2}
+                                    y="0"
+                                    width="1.6"
+                                    height="10"
+                                    fill="none"
+                                    stroke=move || palette.get().grid
+                                    stroke-width="0.1"
+                                    stroke-dasharray="0.3 0.3"
+                                >
+                                    <title>{hour.clone()} ": no data"</title>
+                                </rect>
+                            },
+                        };
+                        view! {
+                            {bar}
+                            {step.lightning.then(|| view! {
+                                <text x={i as f64 * 2.0 + 0.4} y="1.5" font-size="1.5px">"⚡"</text>
+                            })}
+                            {(i % 2 == 0).then(|| view! {
+                                <text x={i as f64 * 2.0} y="12.5" font-size="1.2px">{hour}</text>
+                            })}
+                        }
+                    })
+                    .collect_view()
+                }
+            </svg>
+            <HiddenTable
+                caption="Precipitation every 15 minutes".to_string()
+                headers=vec!["Time", "Precipitation", "Lightning"]
+                rows=table_rows
+            />
+        </div>
+    }
+    .into_view()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steps(precipitation: [f64; STEPS]) -> Vec<Step> {
+        precipitation
+            .into_iter()
+            .map(|mm| Step {
+                // Not a number for steps without data
+                precipitation_mm: Some(mm).filter(|mm| !mm.is_nan()),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn summaries() {
+        assert_eq!(
+            summary(&steps([0.0; STEPS])),
+            "No precipitation in the next two hours."
+        );
+        assert_eq!(
+            summary(&steps([0.0, 0.0, 0.3, 0.5, 0.2, 0.0, 0.0, 0.0])),
+            "Rain starting in ~30 min, lasting ~45 min."
+        );
+        assert_eq!(
+            summary(&steps([0.4, 0.1, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0])),
+            "Rain stopping in ~30 min."
+        );
+        assert_eq!(
+            summary(&steps([0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0])),
+            "Rain starting in ~90 min."
+        );
+    }
+
+    #[test]
+    fn summaries_without_data() {
+        let nan = f64::NAN;
+        assert_eq!(
+            summary(&steps([nan; STEPS])),
+            "No precipitation data for the next two hours."
+        );
+        // Missing data is not taken as dry
+        assert_eq!(
+            summary(&steps([0.0, 0.0, 0.0, nan, 0.0, 0.0, 0.0, 0.0])),
+            "No precipitation in the next ~45 min. No data after that."
+        );
+        assert_eq!(
+            summary(&steps([0.0, 0.3, 0.3, nan, nan, nan, nan, nan])),
+            "Rain starting in ~15 min. No data after that."
+        );
+    }
+
+    #[test]
+    fn steps_start_now() {
+        let minutely_15 = Minutely15 {
+            time: (0..12)
+                .map(|i| format!("2023-11-10T{:02}:{:02}", 14 + i / 4, i % 4 * 15))
+                .collect(),
+            precipitation: (0..12).map(|i| Some(f64::from(i))).collect(),
+            ..Default::default()
+        };
+        let steps = minutely_15.next_steps("2023-11-10T14:15", PrecipitationUnit::Millimetres);
+        assert_eq!(steps.len(), STEPS);
+        assert_eq!(steps[0].0, "2023-11-10T14:30");
+    }
+
+    #[test]
+    fn missing_values_have_no_data() -> Result<(), serde_json::Error> {
+        let minutely_15: Minutely15 = serde_json::from_str(
+            r#"{
+                "time": ["2023-11-10T14:15", "2023-11-10T14:30", "2023-11-10T14:45"],
+                "precipitation": [0.4, null],
+                "snowfall": [null, 0.2]
+            }"#,
+        )?;
+        let steps = minutely_15.next_steps("2023-11-10T14:00", PrecipitationUnit::Millimetres);
+        let amounts: Vec<_> = steps
+            .iter()
+            .map(|(_, step)| step.precipitation_mm)
+            .collect();
+        assert_eq!(amounts, [Some(0.4), None, None]);
+        assert!(!steps[1].1.is_wet());
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+//! Offline support: the service worker (`sw.js`) caches the app, and
+//! [`crate::cache`] the latest forecasts.
+
+use leptos::*;
+
+/// Install the service worker, if the browser supports it.
+pub fn register_service_worker() {
+    let navigator = window().navigator();
+    // Missing in insecure contexts, like plain HTTP other than localhost
+    if !js_sys::Reflect::has(&navigator, &"serviceWorker".into()).unwrap_or(false) {
+        log::info!("service workers are not supported, the app will not work offline");
+        return;
+    }
+    let container = navigator.service_worker();
+    // Relative path, so that it also works under the `docs/` folder of GitHub Pages
+    let registration = container.register("./sw.js");
+    spawn_local(async move {
+        if let Err(e) = wasm_bindgen_futures::JsFuture::from(registration).await {
+            log::warn!("failed to register the service worker: {e:?}");
+        }
+    });
+}
+
+/// Local date and time of a timestamp, in milliseconds since the Unix epoch.
+pub fn local_time(timestamp: f64) -> String {
+    js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(timestamp))
+        .to_locale_string("default", &wasm_bindgen::JsValue::UNDEFINED)
+        .into()
+}
+
+/// Warns that the forecast on screen is outdated, when no newer one can be loaded.
+#[component]
+pub fn StaleBanner(
+    /// Download time of the forecast on screen, when it is outdated.
+    #[prop(into)]
+    stale_since: Signal<Option<f64>>,
+) -> impl IntoView {
+    let (online, set_online) = create_signal(window().navigator().on_line());
+    window_event_listener(ev::online, move |_| set_online.set(true));
+    window_event_listener(ev::offline, move |_| set_online.set(false));
+
+    move || {
+        stale_since.get().map(|timestamp| {
+            view! {
+                <p class="stale" role="status">
+                    {move || if online.get() { "⚠ Could not update the forecast. " } else { "⚠ Offline. " }}
+                    "Stale since " {local_time(timestamp)}
+                </p>
+            }
+        })
+    }
+}
@@ -0,0 +1,365 @@
+//! Drawing backends for charts.
+//!
+//! Charts draw through the [`Painter`] trait, either as SVG elements, or, when
+//! there are too many points for the DOM to stay responsive, on an HTML canvas.
+//! Both backends take the same coordinates, those of the chart's viewBox.
+
+use std::collections::HashMap;
+
+use leptos::*;
+use wasm_bindgen::JsValue;
+
+use crate::{
+    theme::Palette,
+    weather_code::{WeatherCode, WeatherIcon},
+};
+
+/// Above this number of points drawn, charts are drawn on a canvas.
+pub const CANVAS_THRESHOLD: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Svg,
+    Canvas,
+}
+
+impl Backend {
+    pub fn for_points(points: usize) -> Self {
+        if points > CANVAS_THRESHOLD {
+            Backend::Canvas
+        } else {
+            Backend::Svg
+        }
+    }
+}
+
+/// How a shape is filled or stroked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Paint<'a> {
+    None,
+    Color(&'a str),
+    /// A gradient previously defined with [`Painter::gradient`].
+    Gradient(&'static str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+impl Anchor {
+    fn svg(self) -> &'static str {
+        match self {
+            Anchor::Start => "start",
+            Anchor::Middle => "middle",
+            Anchor::End => "end",
+        }
+    }
+
+    fn canvas(self) -> &'static str {
+        match self {
+            Anchor::Start => "left",
+            Anchor::Middle => "center",
+            Anchor::End => "right",
+        }
+    }
+}
+
+pub trait Painter {
+    /// Define a vertical gradient, from (y, colour) stops ordered from top to bottom.
+    fn gradient(&mut self, id: &'static str, stops: &[(f64, &str)], opacity: f64);
+    /// Draw, with `draw`, only inside a rectangle.
+    fn clipped(
+        &mut self,
+        id: &'static str,
+        rect: (f64, f64, f64, f64),
+        draw: impl FnOnce(&mut Self),
+    ) where
+        Self: Sized;
+    /// Draw, with `draw`, lines dashed with a pattern of dash and gap lengths.
+    fn dashed(&mut self, dashes: &'static [f64], draw: impl FnOnce(&mut Self))
+    where
+        Self: Sized;
+    fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, fill: Paint);
+    fn line(&mut self, from: (f64, f64), to: (f64, f64), stroke: Paint, width: f64);
+    /// Draw an SVG path, like the ones built in `crate::series`.
+    fn path(&mut self, d: &str, fill: Paint, stroke: Paint, width: f64);
+    fn text(&mut self, x: f64, y: f64, text: &str, anchor: Anchor, size: f64, bold: bool);
+    fn weather_icon(&mut self, code: WeatherCode, is_day: bool, x: f64, y: f64, size: f64);
+}
+
+impl Paint<'_> {
+    fn svg(self) -> String {
+        match self {
+            Paint::None => "none".to_string(),
+            Paint::Color(color) => color.to_string(),
+            Paint::Gradient(id) => format!("url(#{id})"),
+        }
+    }
+}
+
+/// Draws SVG elements, to be placed inside an `<svg>`.
+#[derive(Default)]
+pub struct SvgPainter {
+    views: Vec<View>,
+}
+
+impl SvgPainter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IntoView for SvgPainter {
+    fn into_view(self) -> View {
+        self.views.into_view()
+    }
+}
+
+impl Painter for SvgPainter {
+    fn gradient(&mut self, id: &'static str, stops: &[(f64, &str)], opacity: f64) {
+        let stops = stops
+            .iter()
+            .map(|(y, color)| (*y, color.to_string()))
+            .collect();
+        self.views.push(
+            view! {
+                <defs>
+                    <crate::series::VerticalGradient id stops opacity/>
+                </defs>
+            }
+            .into_view(),
+        );
+    }
+
+    fn clipped(
+        &mut self,
+        id: &'static str,
+        (x, y, width, height): (f64, f64, f64, f64),
+        draw: impl FnOnce(&mut Self),
+    ) {
+        let mut inner = SvgPainter::new();
+        draw(&mut inner);
+        self.views.push(
+            view! {
+                <clipPath id=id>
+                    <rect x=x y=y width=width height=height/>
+                </clipPath>
+                <g clip-path=format!("url(#{id})")>{inner.views}</g>
+            }
+            .into_view(),
+        );
+    }
+
+    fn dashed(&mut self, dashes: &'static [f64], draw: impl FnOnce(&mut Self)) {
+        let mut inner = SvgPainter::new();
+        draw(&mut inner);
+        let dashes = dashes
+            .iter()
+            .map(f64::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.views
+            .push(view! { <g stroke-dasharray=dashes>{inner.views}</g> }.into_view());
+    }
+
+    fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, fill: Paint) {
+        self.views
+            .push(view! { <rect x=x y=y width=width height=height fill=fill.svg()/> }.into_view());
+    }
+
+    fn line(&mut self, (x1, y1): (f64, f64), (x2, y2): (f64, f64), stroke: Paint, width: f64) {
+        self.views.push(
+            view! { <line x1=x1 y1=y1 x2=x2 y2=y2 stroke=stroke.svg() stroke-width=width/> }
+                .into_view(),
+        );
+    }
+
+    fn path(&mut self, d: &str, fill: Paint, stroke: Paint, width: f64) {
+        self.views.push(
+            view! {
+                <path
+                    d=d.to_string()
+                    fill=fill.svg()
+                    stroke=stroke.svg()
+                    stroke-width=width
+                    stroke-linecap="round"
+                    stroke-linejoin="round"
+                />
+            }
+            .into_view(),
+        );
+    }
+
+    fn text(&mut self, x: f64, y: f64, text: &str, anchor: Anchor, size: f64, bold: bool) {
+        self.views.push(
+            view! {
+                <text
+                    x=x
+                    y=y
+                    text-anchor=anchor.svg()
+                    font-size=format!("{size}px")
+                    font-weight=if bold { "600" } else { "normal" }
+                >
+                    {text.to_string()}
+                </text>
+            }
+            .into_view(),
+        );
+    }
+
+    fn weather_icon(&mut self, code: WeatherCode, is_day: bool, x: f64, y: f64, size: f64) {
+        self.views
+            .push(view! { <WeatherIcon code is_day x y size/> }.into_view());
+    }
+}
+
+/// Draws on a canvas, whose context is already scaled to the chart's viewBox.
+pub struct CanvasPainter {
+    context: web_sys::CanvasRenderingContext2d,
+    gradients: HashMap<&'static str, (web_sys::CanvasGradient, f64)>,
+    /// Colours of the text and of the weather symbols.
+    palette: Palette,
+}
+
+impl CanvasPainter {
+    pub fn new(context: web_sys::CanvasRenderingContext2d, palette: Palette) -> Self {
+        CanvasPainter {
+            context,
+            gradients: HashMap::new(),
+            palette,
+        }
+    }
+
+    /// Set the style of the next fill (or stroke), returning whether there is one.
+    fn set_paint(&self, paint: Paint, stroke: bool) -> bool {
+        let (style, alpha) = match paint {
+            Paint::None => return false,
+            Paint::Color(color) => (JsValue::from_str(color), 1.0),
+            Paint::Gradient(id) => match self.gradients.get(id) {
+                Some((gradient, opacity)) => (gradient.into(), *opacity),
+                None => return false,
+            },
+        };
+        if stroke {
+            self.context.set_stroke_style(&style);
+        } else {
+            self.context.set_fill_style(&style);
+        }
+        self.context.set_global_alpha(alpha);
+        true
+    }
+}
+
+impl Painter for CanvasPainter {
+    fn gradient(&mut self, id: &'static str, stops: &[(f64, &str)], opacity: f64) {
+        let (top, bottom) = match (stops.first(), stops.last()) {
+            (Some((top, _)), Some((bottom, _))) if bottom > top => (*top, *bottom),
+            _ => return,
+        };
+        let gradient = self.context.create_linear_gradient(0.0, top, 0.0, bottom);
+        for (y, color) in stops {
+            let offset = ((y - top) / (bottom - top)) as f32;
+            if gradient.add_color_stop(offset, color).is_err() {
+                log::warn!("invalid gradient colour {color}");
+            }
+        }
+        self.gradients.insert(id, (gradient, opacity));
+    }
+
+    fn clipped(
+        &mut self,
+        _id: &'static str,
+        (x, y, width, height): (f64, f64, f64, f64),
+        draw: impl FnOnce(&mut Self),
+    ) {
+        self.context.save();
+        self.context.begin_path();
+        self.context.rect(x, y, width, height);
+        self.context.clip();
+        draw(self);
+        self.context.restore();
+    }
+
+    fn dashed(&mut self, dashes: &'static [f64], draw: impl FnOnce(&mut Self)) {
+        let dashes: js_sys::Array = dashes.iter().map(|&dash| JsValue::from(dash)).collect();
+        self.context.save();
+        if self.context.set_line_dash(&dashes).is_err() {
+            log::warn!("invalid line dashes");
+        }
+        draw(self);
+        self.context.restore();
+    }
+
+    fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, fill: Paint) {
+        if self.set_paint(fill, false) {
+            self.context.fill_rect(x, y, width, height);
+        }
+    }
+
+    fn line(&mut self, (x1, y1): (f64, f64), (x2, y2): (f64, f64), stroke: Paint, width: f64) {
+        if self.set_paint(stroke, true) {
+            self.context.set_line_width(width);
+            self.context.begin_path();
+            self.context.move_to(x1, y1);
+            self.context.line_to(x2, y2);
+            self.context.stroke();
+        }
+    }
+
+    fn path(&mut self, d: &str, fill: Paint, stroke: Paint, width: f64) {
+        let Ok(path) = web_sys::Path2d::new_with_path_string(d) else {
+            log::warn!("invalid path {d}");
+            return;
+        };
+        if self.set_paint(fill, false) {
+            self.context.fill_with_path_2d(&path);
+        }
+        if self.set_paint(stroke, true) {
+            self.context.set_line_width(width);
+            self.context.set_line_cap("round");
+            self.context.set_line_join("round");
+            self.context.stroke_with_path(&path);
+        }
+    }
+
+    fn text(&mut self, x: f64, y: f64, text: &str, anchor: Anchor, size: f64, bold: bool) {
+        let weight = if bold { "600" } else { "normal" };
+        self.context
+            .set_font(&format!("{weight} {size}px sans-serif"));
+        self.context.set_text_align(anchor.canvas());
+        self.set_paint(Paint::Color(self.palette.text), false);
+        let _ = self.context.fill_text(text, x, y);
+    }
+
+    fn weather_icon(&mut self, code: WeatherCode, is_day: bool, x: f64, y: f64, size: f64) {
+        // The same shapes as the SVG symbol, in its 10 x 10 box
+        self.context.save();
+        let scale = size / 10.0;
+        if self.context.translate(x, y).is_ok() && self.context.scale(scale, scale).is_ok() {
+            for shape in code.icon_shapes(is_day) {
+                let fill = shape
+                    .fill
+                    .map_or(Paint::None, |color| Paint::Color(color.of(&self.palette)));
+                let (stroke, width) = shape.stroke.map_or((Paint::None, 0.0), |(color, width)| {
+                    (Paint::Color(color.of(&self.palette)), width)
+                });
+                self.path(&shape.d, fill, stroke, width);
+            }
+        }
+        self.context.restore();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backend_switches_above_threshold() {
+        assert_eq!(Backend::for_points(48 * 4), Backend::Svg);
+        assert_eq!(Backend::for_points(384 * 4), Backend::Canvas);
+    }
+}
@@ -0,0 +1,182 @@
+//! Shareable links: the query shown on screen is mirrored in the URL query string,
+//! for example `?lat=48.3&lon=16.3&days=5&units=celsius,kmh,mm`.
+//!
+//! Only the query string is touched, so links keep working wherever the app is
+//! served from, including the relative paths of the GitHub Pages deployment.
+//!
+//! The URL follows the sliders once they settle, with one history entry per
+//! query looked at. The chart view and zoom are not part of links: they are
+//! reset whenever a new forecast is shown anyway.
+
+use leptos::*;
+
+use crate::{fetch, units::Units};
+
+/// Query parameters understood in the URL. Missing or invalid values are `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Permalink {
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub forecast_days: Option<usize>,
+    pub units: Option<Units>,
+}
+
+impl Permalink {
+    /// Parse a query string, with or without the leading `?`.
+    pub fn parse(query: &str) -> Self {
+        let mut permalink = Self::default();
+        for (key, value) in query
+            .trim_start_matches('?')
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+        {
+            match key {
+                "lat" => {
+                    permalink.latitude = value
+                        .parse()
+                        .ok()
+                        .filter(|v: &f64| (-90.0..=90.0).contains(v))
+                }
+                "lon" => {
+                    permalink.longitude = value
+                        .parse()
+                        .ok()
+                        .filter(|v: &f64| (-180.0..=180.0).contains(v))
+                }
+                "days" => {
+                    permalink.forecast_days =
+                        value.parse().ok().filter(|v: &usize| (1..=16).contains(v))
+                }
+                "units" => permalink.units = Units::parse_link_value(value),
+                _ => log::debug!("ignoring unknown query parameter {key}"),
+            }
+        }
+        permalink
+    }
+
+    /// Query string, with the leading `?`.
+    pub fn query(&self) -> String {
+        let parameters = [
+            self.latitude.map(|v| format!("lat={v}")),
+            self.longitude.map(|v| format!("lon={v}")),
+            self.forecast_days.map(|v| format!("days={v}")),
+            self.units.map(|v| format!("units={}", v.link_value())),
+        ];
+        let parameters: Vec<_> = parameters.into_iter().flatten().collect();
+        format!("?{}", parameters.join("&"))
+    }
+
+    /// Parameters of the page URL.
+    pub fn current() -> Self {
+        Self::parse(&window().location().search().unwrap_or_default())
+    }
+
+    /// Add a history entry for these parameters, unless the URL already shows them.
+    pub fn push(&self) {
+        if Self::current() == *self {
+            return;
+        }
+        let result = window().history().and_then(|history| {
+            history.push_state_with_url(&wasm_bindgen::JsValue::NULL, "", Some(&self.query()))
+        });
+        if result.is_err() {
+            log::warn!("failed to update the page URL");
+        }
+    }
+
+    /// Show these parameters in the URL of the current history entry.
+    pub fn replace(&self) {
+        let result = window().history().and_then(|history| {
+            history.replace_state_with_url(&wasm_bindgen::JsValue::NULL, "", Some(&self.query()))
+        });
+        if result.is_err() {
+            log::warn!("failed to update the page URL");
+        }
+    }
+}
+
+/// Keep the signals and the page URL in sync, in both directions.
+pub fn sync_with_url(
+    (latitude, set_latitude): (ReadSignal<f64>, WriteSignal<f64>),
+    (longitude, set_longitude): (ReadSignal<f64>, WriteSignal<f64>),
+    (forecast_days, set_forecast_days): (ReadSignal<usize>, WriteSignal<usize>),
+    (units, set_units): (ReadSignal<Units>, WriteSignal<Units>),
+) {
+    let shown = move || Permalink {
+        latitude: Some(latitude.get()),
+        longitude: Some(longitude.get()),
+        forecast_days: Some(forecast_days.get()),
+        units: Some(units.get()),
+    };
+    // Sliders change many times per second: one entry once they settle
+    let settled = fetch::debounced(shown, fetch::DEBOUNCE_DELAY);
+    create_effect(move |previous: Option<()>| {
+        let permalink = settled.get();
+        if previous.is_none() {
+            // Completes the URL the app was opened with, without adding an entry
+            permalink.replace();
+        } else {
+            permalink.push();
+        }
+    });
+    // Back and forward navigation
+    window_event_listener(ev::popstate, move |_| {
+        let permalink = Permalink::current();
+        log::trace!("restoring {permalink:?}");
+        batch(|| {
+            if let Some(v) = permalink.latitude {
+                set_latitude.set(v);
+            }
+            if let Some(v) = permalink.longitude {
+                set_longitude.set(v);
+            }
+            if let Some(v) = permalink.forecast_days {
+                set_forecast_days.set(v);
+            }
+            if let Some(v) = permalink.units {
+                set_units.set(v);
+            }
+        });
+        // Parameters the entry leaves out keep their values: show them, so that
+        // the entry is not pushed again once the restored values settle
+        untrack(shown).replace();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::WindSpeedUnit;
+
+    #[test]
+    fn query_round_trip() {
+        let permalink = Permalink {
+            latitude: Some(48.3),
+            longitude: Some(-16.3),
+            forecast_days: Some(5),
+            units: Some(Units {
+                wind_speed: WindSpeedUnit::Beaufort,
+                ..Units::METRIC
+            }),
+        };
+        assert_eq!(
+            permalink.query(),
+            "?lat=48.3&lon=-16.3&days=5&units=celsius,bft,mm"
+        );
+        assert_eq!(Permalink::parse(&permalink.query()), permalink);
+        let imperial = Permalink::parse("units=fahrenheit,mph,inch");
+        assert_eq!(imperial.units, Some(Units::IMPERIAL));
+    }
+
+    #[test]
+    fn invalid_parameters_are_ignored() {
+        let permalink = Permalink::parse("lat=91&lon=abc&days=5&units=celsius,kmh&zoom=3");
+        assert_eq!(
+            permalink,
+            Permalink {
+                forecast_days: Some(5),
+                ..Default::default()
+            }
+        );
+    }
+}
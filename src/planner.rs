@@ -0,0 +1,85 @@
+//! Golden and blue hours of each day, for photographers, with the moon.
+
+use leptos::*;
+
+use crate::{
+    data::weekday,
+    moon::MoonDay,
+    sun::{format_hour, SunDay},
+};
+
+/// Periods of a day, like "06:21–06:34, 16:52–17:05".
+fn format_periods(periods: &[(f64, f64)]) -> String {
+    if periods.is_empty() {
+        return "–".to_string();
+    }
+    periods
+        .iter()
+        .map(|&(from, to)| format!("{}–{}", format_hour(from), format_hour(to)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[component]
+pub fn PhotoPlanner(
+    /// Local dates, like "2023-11-10".
+    dates: Vec<String>,
+    latitude: f64,
+    longitude: f64,
+    utc_offset_seconds: f64,
+) -> impl IntoView {
+    view! {
+        <details class="planner">
+            <summary>"Golden and blue hours 📷"</summary>
+            <table>
+                <thead>
+                    <tr>
+                        <th scope="col">"Day"</th>
+                        <th scope="col">"Blue hour"</th>
+                        <th scope="col">"Golden hour"</th>
+                        <th scope="col">"Moon"</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {dates
+                        .into_iter()
+                        .filter_map(|date| {
+                            let sun = SunDay::new(latitude, longitude, &date, utc_offset_seconds)?;
+                            let moon = MoonDay::new(latitude, longitude, &date, utc_offset_seconds)?;
+                            let moon_times = format!(
+                                "↑ {} ↓ {}",
+                                moon.moonrise.map(format_hour).unwrap_or("–".to_string()),
+                                moon.moonset.map(format_hour).unwrap_or("–".to_string()),
+                            );
+                            Some(view! {
+                                <tr>
+                                    <th scope="row">{weekday(&date).unwrap_or_default()} " " {date.get(8..10).unwrap_or_default().to_string()}</th>
+                                    <td>{format_periods(&sun.blue_hours)}</td>
+                                    <td>{format_periods(&sun.golden_hours)}</td>
+                                    <td title=format!("{}, {:.0}% lit", moon.phase.name(), moon.phase.illumination * 100.0)>
+                                        {moon.phase.emoji()} " " {moon_times}
+                                    </td>
+                                </tr>
+                            })
+                        })
+                        .collect_view()
+                    }
+                </tbody>
+            </table>
+        </details>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn periods() {
+        assert_eq!(format_periods(&[]), "–");
+        assert_eq!(
+            format_periods(&[(6.35, 6.575), (16.865, 17.09)]),
+            "06:21–06:35, 16:52–17:05"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Periodic refresh of the forecast on screen.
+
+use std::time::Duration;
+
+use leptos::*;
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "weather.refresh_minutes";
+/// Auto-refresh intervals offered, in minutes. Zero disables auto-refresh.
+const INTERVALS: [u32; 5] = [0, 10, 15, 30, 60];
+const DEFAULT_INTERVAL: u32 = 30;
+/// How often the "updated ... ago" indicator is updated and the interval checked.
+const TICK: Duration = Duration::from_secs(30);
+
+/// Human description of the time elapsed since an update.
+pub fn updated_ago(minutes: f64) -> String {
+    let minutes = minutes.max(0.0).floor() as u64;
+    match minutes {
+        0 => "just now".to_string(),
+        1 => "1 minute ago".to_string(),
+        2..=59 => format!("{minutes} minutes ago"),
+        60..=119 => "1 hour ago".to_string(),
+        _ => format!("{} hours ago", minutes / 60),
+    }
+}
+
+#[component]
+pub fn Refresh(
+    /// Download time of the forecast on screen, in milliseconds since the Unix epoch.
+    #[prop(into)]
+    updated_at: Signal<Option<f64>>,
+    /// Download the forecast again.
+    #[prop(into)]
+    on_refresh: Callback<()>,
+) -> impl IntoView {
+    let (interval, set_interval_minutes) =
+        create_signal(storage::get(STORAGE_KEY).unwrap_or(DEFAULT_INTERVAL));
+    create_effect(move |_| storage::set(STORAGE_KEY, &interval.get()));
+    let (now, set_now) = create_signal(js_sys::Date::now());
+
+    // Nothing happens while the tab is hidden
+    let tick = move || {
+        if document().hidden() {
+            return;
+        }
+        let now = js_sys::Date::now();
+        set_now.set(now);
+        let minutes = interval.get_untracked();
+        let due = updated_at
+            .get_untracked()
+            .is_some_and(|updated_at| now - updated_at >= f64::from(minutes) * 60_000.0);
+        if minutes > 0 && due {
+            log::trace!("auto-refreshing the forecast");
+            on_refresh.call(());
+        }
+    };
+    if let Ok(handle) = set_interval_with_handle(tick, TICK) {
+        on_cleanup(move || handle.clear());
+    }
+    // Catch up as soon as the tab is visible again.
+    // The event is fired at the document and bubbles up to the window.
+    window_event_listener_untyped("visibilitychange", move |_| tick());
+    // Keep the indicator consistent with a newly loaded forecast
+    create_effect(move |_| {
+        updated_at.track();
+        set_now.set(js_sys::Date::now());
+    });
+
+    view! {
+        <div class="refresh">
+            {move || updated_at.get().map(|updated_at| view! {
+                <span title={crate::offline::local_time(updated_at)}>
+                    "Updated " {move || updated_ago((now.get() - updated_at) / 60_000.0)}
+                </span>
+            })}
+            <button type="button" on:click=move |_| on_refresh.call(())>"Refresh 🔄"</button>
+            <label>
+                "Auto-refresh: "
+                <select on:change=move |ev| {
+                    let v = event_target_value(&ev).parse().unwrap_or(DEFAULT_INTERVAL);
+                    log::trace!("setting auto-refresh interval to {v} minutes");
+                    set_interval_minutes.set(v);
+                }>
+                    {INTERVALS
+                        .into_iter()
+                        .map(|minutes| view! {
+                            <option value=minutes prop:selected=move || interval.get() == minutes>
+                                {if minutes == 0 { "off".to_string() } else { format!("every {minutes} min") }}
+                            </option>
+                        })
+                        .collect_view()
+                    }
+                </select>
+            </label>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elapsed_time_description() {
+        assert_eq!(updated_ago(-0.5), "just now");
+        assert_eq!(updated_ago(1.7), "1 minute ago");
+        assert_eq!(updated_ago(42.0), "42 minutes ago");
+        assert_eq!(updated_ago(61.0), "1 hour ago");
+        assert_eq!(updated_ago(150.0), "2 hours ago");
+    }
+}
@@ -0,0 +1,236 @@
+//! SVG paths drawing a series of points: straight or smoothed lines, filled areas
+//! and bands.
+//!
+//! Points whose value is missing (not finite) split the series, so that gaps in the
+//! data show as gaps in the chart.
+
+use leptos::*;
+
+/// Runs of consecutive points with a finite value.
+pub fn segments(points: &[(f64, f64)]) -> Vec<&[(f64, f64)]> {
+    points
+        .split(|(_, y)| !y.is_finite())
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+/// Straight lines between the points.
+pub fn line_path(points: &[(f64, f64)]) -> String {
+    segments(points)
+        .into_iter()
+        .map(|segment| {
+            let (x, y) = segment[0];
+            segment[1..]
+                .iter()
+                .fold(format!("M{x} {y}"), |path, (x, y)| {
+                    path + &format!("L{x} {y}")
+                })
+        })
+        .collect()
+}
+
+/// Smooth curve through the points.
+///
+/// The curve is a monotone cubic interpolation: it never overshoots the values
+/// between two points, so that a dry hour is never drawn below zero.
+pub fn smooth_path(points: &[(f64, f64)]) -> String {
+    segments(points)
+        .into_iter()
+        .map(|segment| {
+            let (x, y) = segment[0];
+            let start = format!("M{x} {y}");
+            if segment.len() == 1 {
+                // A lone value, drawn as a dot by round line caps
+                return start + "h0";
+            }
+            let tangents = monotone_tangents(segment);
+            segment
+                .windows(2)
+                .zip(tangents.windows(2))
+                .fold(start, |path, (points, tangents)| {
+                    let ((x0, y0), (x1, y1)) = (points[0], points[1]);
+                    let third = (x1 - x0) / 3.0;
+                    path + &format!(
+                        "C{} {} {} {} {x1} {y1}",
+                        x0 + third,
+                        y0 + tangents[0] * third,
+                        x1 - third,
+                        y1 - tangents[1] * third,
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Closed area between the line through the points and a horizontal baseline.
+pub fn area_path(points: &[(f64, f64)], baseline: f64) -> String {
+    segments(points)
+        .into_iter()
+        .map(|segment| {
+            let (first, last) = (segment[0].0, segment[segment.len() - 1].0);
+            let outline = segment
+                .iter()
+                .map(|(x, y)| format!("L{x} {y}"))
+                .collect::<String>();
+            format!("M{first} {baseline}{outline}L{last} {baseline}Z")
+        })
+        .collect()
+}
+
+/// Closed area between two lines, like a range of values. Points are (x, top,
+/// bottom), and those missing either value split the area.
+pub fn band_path(points: &[(f64, f64, f64)]) -> String {
+    points
+        .split(|(_, top, bottom)| !top.is_finite() || !bottom.is_finite())
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            let (x, top, _) = segment[0];
+            let top = segment[1..]
+                .iter()
+                .fold(format!("M{x} {top}"), |path, (x, top, _)| {
+                    path + &format!("L{x} {top}")
+                });
+            let bottom = segment
+                .iter()
+                .rev()
+                .map(|(x, _, bottom)| format!("L{x} {bottom}"))
+                .collect::<String>();
+            format!("{top}{bottom}Z")
+        })
+        .collect()
+}
+
+/// Slopes at each point for a monotone cubic interpolation (Fritsch–Carlson).
+fn monotone_tangents(points: &[(f64, f64)]) -> Vec<f64> {
+    let slopes: Vec<f64> = points
+        .windows(2)
+        .map(|w| (w[1].1 - w[0].1) / (w[1].0 - w[0].0))
+        .collect();
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            if i == 0 {
+                slopes[0]
+            } else if i == n - 1 {
+                slopes[n - 2]
+            } else {
+                let (before, after) = (slopes[i - 1], slopes[i]);
+                if before * after <= 0.0 {
+                    // Local extremum: flat, to not overshoot
+                    0.0
+                } else {
+                    // Harmonic mean, which stays within the monotone region
+                    2.0 / (1.0 / before + 1.0 / after)
+                }
+            }
+        })
+        .collect()
+}
+
+/// Shape marking a point, so that its category does not rely on colour alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Marker {
+    Circle,
+    Triangle,
+    Diamond,
+}
+
+impl Marker {
+    /// Closed path of the marker, `size` wide, centred on (x, y).
+    pub fn path(self, x: f64, y: f64, size: f64) -> String {
+        let r = size / 2.0;
+        match self {
+            Marker::Circle => format!(
+                "M{} {y}a{r} {r} 0 1 0 {size} 0a{r} {r} 0 1 0 {} 0Z",
+                x - r,
+                -size
+            ),
+            Marker::Triangle => format!("M{x} {}L{} {}L{} {}Z", y - r, x + r, y + r, x - r, y + r),
+            Marker::Diamond => format!("M{x} {}L{} {y}L{x} {}L{} {y}Z", y - r, x + r, y + r, x - r),
+        }
+    }
+}
+
+/// Vertical gradient, to colour a line or an area by its value.
+///
+/// Stops are given as (y, colour), in the coordinates of the chart, from top to bottom.
+#[component]
+pub fn VerticalGradient(
+    id: &'static str,
+    stops: Vec<(f64, String)>,
+    /// Opacity of each stop, if not opaque.
+    #[prop(optional)]
+    opacity: Option<f64>,
+) -> impl IntoView {
+    let (top, bottom) = match (stops.first(), stops.last()) {
+        (Some((top, _)), Some((bottom, _))) if bottom > top => (*top, *bottom),
+        _ => (0.0, 1.0),
+    };
+    view! {
+        <linearGradient id=id gradientUnits="userSpaceOnUse" x1="0" x2="0" y1=top y2=bottom>
+            {stops
+                .into_iter()
+                .map(|(y, color)| view! {
+                    <stop
+                        offset={(y - top) / (bottom - top)}
+                        style={format!("stop-color: {color}; stop-opacity: {}", opacity.unwrap_or(1.0))}
+                    />
+                })
+                .collect_view()
+            }
+        </linearGradient>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_values_split_lines() {
+        let points = [(0.0, 1.0), (1.0, 2.0), (2.0, f64::NAN), (3.0, 4.0)];
+        assert_eq!(segments(&points).len(), 2);
+        assert_eq!(line_path(&points), "M0 1L1 2M3 4");
+        assert_eq!(
+            area_path(&points, 10.0),
+            "M0 10L0 1L1 2L1 10ZM3 10L3 4L3 10Z"
+        );
+        assert_eq!(smooth_path(&[(0.0, f64::NAN)]), "");
+        assert_eq!(
+            band_path(&[
+                (0.0, 1.0, 3.0),
+                (1.0, 2.0, 3.0),
+                (2.0, f64::NAN, 3.0),
+                (3.0, 1.0, 2.0)
+            ]),
+            "M0 1L1 2L1 3L0 3ZM3 1L3 2Z"
+        );
+    }
+
+    #[test]
+    fn markers() {
+        assert_eq!(Marker::Triangle.path(5.0, 5.0, 2.0), "M5 4L6 6L4 6Z");
+        assert_eq!(Marker::Diamond.path(5.0, 5.0, 2.0), "M5 4L6 5L5 6L4 5Z");
+        assert_eq!(
+            Marker::Circle.path(5.0, 5.0, 2.0),
+            "M4 5a1 1 0 1 0 2 0a1 1 0 1 0 -2 0Z"
+        );
+    }
+
+    #[test]
+    fn smoothing_does_not_overshoot() {
+        // A peak, then a plateau
+        let points = [(0.0, 0.0), (1.0, 10.0), (2.0, 0.0), (3.0, 0.0), (4.0, 0.0)];
+        let tangents = monotone_tangents(&points);
+        assert_eq!(tangents[1], 0.0);
+        assert_eq!(tangents[3], 0.0);
+        // Control points stay between the values they join
+        for (w, t) in points.windows(2).zip(tangents.windows(2)) {
+            let third = (w[1].0 - w[0].0) / 3.0;
+            let (low, high) = (w[0].1.min(w[1].1), w[0].1.max(w[1].1));
+            for y in [w[0].1 + t[0] * third, w[1].1 - t[1] * third] {
+                assert!(low <= y && y <= high, "{y} outside {low}..{high}");
+            }
+        }
+    }
+}
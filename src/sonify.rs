@@ -0,0 +1,201 @@
+//! Sonification of a forecast, for people who cannot see the charts.
+//!
+//! Hours are played one after the other over a few seconds. The temperature is
+//! a tone, higher the warmer it is, and precipitation a burst of noise, louder
+//! the more it rains. The noise comes from a seeded generator, so that the same
+//! forecast always gives the same samples, whether they are played or saved as
+//! a WAV file.
+
+/// Samples per second, enough for tones of a few hundred hertz.
+pub const SAMPLE_RATE: u32 = 22_050;
+/// Length of the sound, in seconds, whatever the number of hours.
+pub const DURATION: f64 = 6.0;
+/// Pitches of the lowest and highest temperatures, in Hz: two octaves from A3.
+pub const PITCH_RANGE: (f64, f64) = (220.0, 880.0);
+/// Precipitation at which the noise is loudest, in mm per hour: heavy rain.
+pub const HEAVY_PRECIPITATION_MM: f64 = 5.0;
+const TONE_LEVEL: f64 = 0.5;
+const NOISE_LEVEL: f64 = 0.4;
+/// Fade in and out, in seconds, to not start or stop with a click.
+const FADE: f64 = 0.01;
+const NOISE_SEED: u32 = 0x2545_f491;
+
+/// Pitch of a temperature, in Hz, on a scale from the lowest to the highest
+/// temperature, evenly spread over the semitones of [`PITCH_RANGE`].
+pub fn pitch(temperature: f64, (lowest, highest): (f64, f64)) -> f64 {
+    let (low, high) = PITCH_RANGE;
+    let fraction = if highest > lowest {
+        ((temperature - lowest) / (highest - lowest)).clamp(0.0, 1.0)
+    } else {
+        0.5
+    };
+    low * (high / low).powf(fraction)
+}
+
+/// Deterministic white noise, from -1 to 1 (xorshift).
+struct Noise(u32);
+
+impl Noise {
+    fn next(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        f64::from(self.0) / f64::from(u32::MAX) * 2.0 - 1.0
+    }
+}
+
+/// Samples, from -1 to 1, of the sound of hourly temperatures and precipitation.
+///
+/// Missing values are silent: no tone without a temperature, and no noise
+/// without precipitation.
+pub fn render(temperatures: &[f64], precipitation_mm: &[f64]) -> Vec<f32> {
+    let hours = temperatures.len();
+    let length = (f64::from(SAMPLE_RATE) * DURATION) as usize;
+    if hours == 0 {
+        return vec![0.0; length];
+    }
+    let range = temperatures
+        .iter()
+        .filter(|t| t.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), &t| {
+            (low.min(t), high.max(t))
+        });
+    let samples_per_hour = length as f64 / hours as f64;
+    let mut noise = Noise(NOISE_SEED);
+    let mut phase = 0.0;
+    (0..length)
+        .map(|i| {
+            let position = i as f64 / samples_per_hour;
+            let hour = (position as usize).min(hours - 1);
+            let within = position - hour as f64;
+            // The pitch glides from one hour to the next
+            let next = temperatures.get(hour + 1).copied().unwrap_or(f64::NAN);
+            let temperature = temperatures[hour];
+            let temperature = if next.is_finite() {
+                temperature + (next - temperature) * within
+            } else {
+                temperature
+            };
+            let tone = if temperature.is_finite() {
+                phase = (phase + pitch(temperature, range) / f64::from(SAMPLE_RATE)).fract();
+                (phase * std::f64::consts::TAU).sin() * TONE_LEVEL
+            } else {
+                0.0
+            };
+            // A burst for each hour, fading until the next one
+            let precipitation = precipitation_mm.get(hour).copied().unwrap_or(0.0);
+            let loudness = if precipitation.is_finite() {
+                (precipitation / HEAVY_PRECIPITATION_MM).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let burst = noise.next() * loudness * (1.0 - within) * NOISE_LEVEL;
+            let seconds = i as f64 / f64::from(SAMPLE_RATE);
+            let fade = (seconds / FADE)
+                .min((DURATION - seconds) / FADE)
+                .clamp(0.0, 1.0);
+            ((tone + burst) * fade).clamp(-1.0, 1.0) as f32
+        })
+        .collect()
+}
+
+/// A WAV file of mono samples at [`SAMPLE_RATE`], as 16-bit PCM.
+pub fn wav(samples: &[f32]) -> Vec<u8> {
+    const BYTES_PER_SAMPLE: u16 = 2;
+    let data_size = samples.len() as u32 * u32::from(BYTES_PER_SAMPLE);
+    let mut bytes = Vec::with_capacity(44 + data_size as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    // Size of the format chunk, PCM, one channel
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * u32::from(BYTES_PER_SAMPLE)).to_le_bytes());
+    bytes.extend_from_slice(&BYTES_PER_SAMPLE.to_le_bytes());
+    bytes.extend_from_slice(&(BYTES_PER_SAMPLE * 8).to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)).round() as i16;
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zero_crossings(samples: &[f32]) -> usize {
+        samples
+            .windows(2)
+            .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+            .count()
+    }
+
+    #[test]
+    fn pitches() {
+        assert_eq!(pitch(-5.0, (-5.0, 15.0)), 220.0);
+        assert!((pitch(5.0, (-5.0, 15.0)) - 440.0).abs() < 1e-9);
+        assert!((pitch(15.0, (-5.0, 15.0)) - 880.0).abs() < 1e-9);
+        // Constant temperatures are in the middle
+        assert!((pitch(3.0, (3.0, 3.0)) - 440.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn deterministic() {
+        let temperatures: Vec<f64> = (0..48).map(|i| (i as f64 / 4.0).sin() * 10.0).collect();
+        let precipitation: Vec<f64> = (0..48).map(|i| (i % 7) as f64).collect();
+        let samples = render(&temperatures, &precipitation);
+        assert_eq!(samples.len(), (f64::from(SAMPLE_RATE) * DURATION) as usize);
+        assert_eq!(samples, render(&temperatures, &precipitation));
+        assert!(samples.iter().all(|s| (-1.0..=1.0).contains(s)));
+    }
+
+    #[test]
+    fn tone_follows_temperature() {
+        let dry = [0.0; 24];
+        // Constant temperature: a pure tone at 440 Hz, two crossings per period
+        let crossings = zero_crossings(&render(&[10.0; 24], &dry));
+        let expected = 2.0 * 440.0 * DURATION;
+        assert!((crossings as f64 - expected).abs() < 10.0, "{crossings}");
+        // Warming: low first, high at the end
+        let warming: Vec<f64> = (0..24).map(f64::from).collect();
+        let samples = render(&warming, &dry);
+        let (first, last) = samples.split_at(samples.len() / 2);
+        assert!(zero_crossings(first) < zero_crossings(last));
+        // No temperature, no tone
+        assert!(render(&[f64::NAN; 24], &dry).iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn noise_follows_precipitation() {
+        let energy = |precipitation: &[f64]| -> f64 {
+            render(&[f64::NAN; 4], precipitation)
+                .iter()
+                .map(|&s| f64::from(s).powi(2))
+                .sum()
+        };
+        assert_eq!(energy(&[0.0; 4]), 0.0);
+        assert!(energy(&[1.0; 4]) > 0.0);
+        assert!(energy(&[4.0; 4]) > energy(&[1.0; 4]) * 10.0);
+    }
+
+    #[test]
+    fn wav_header() {
+        let bytes = wav(&[0.0, 1.0, -1.0]);
+        assert_eq!(bytes.len(), 44 + 6);
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()), 42);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(
+            u32::from_le_bytes(bytes[24..28].try_into().unwrap()),
+            SAMPLE_RATE
+        );
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(u32::from_le_bytes(bytes[40..44].try_into().unwrap()), 6);
+        assert_eq!(&bytes[44..], [0, 0, 0xff, 0x7f, 0x01, 0x80]);
+    }
+}
@@ -0,0 +1,39 @@
+//! Small helpers around the browser's local storage.
+//!
+//! Values are stored as JSON under a key. Any failure (no storage available,
+//! quota exceeded, malformed content) is logged and otherwise ignored: losing
+//! a preference must never break the app.
+
+use serde::{de::DeserializeOwned, Serialize};
+
+fn local_storage() -> Option<web_sys::Storage> {
+    leptos::window().local_storage().ok().flatten()
+}
+
+/// Read the value stored under `key`, if any.
+pub fn get<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let raw = local_storage()?.get_item(key).ok().flatten()?;
+    match serde_json::from_str(&raw) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            log::warn!("ignoring malformed local storage entry {key}: {e}");
+            None
+        }
+    }
+}
+
+/// Store `value` under `key`.
+pub fn set<T: Serialize>(key: &str, value: &T) {
+    let Some(storage) = local_storage() else {
+        log::warn!("local storage is not available");
+        return;
+    };
+    match serde_json::to_string(value) {
+        Ok(raw) => {
+            if storage.set_item(key, &raw).is_err() {
+                log::warn!("failed to write local storage entry {key}");
+            }
+        }
+        Err(e) => log::warn!("failed to serialize local storage entry {key}: {e}"),
+    }
+}
@@ -0,0 +1,431 @@
+//! Position of the sun, and the times of its daily events, computed locally for
+//! any place and date.
+//!
+//! Sunrise and sunset times from the API cannot describe polar day or polar
+//! night, so the charts shade nights from the elevation of the sun instead.
+//! The position is the low precision one of the Astronomical Almanac, good to
+//! about 0.01° between 1950 and 2050, so events are within a minute or so.
+//!
+//! Times are counted in days since J2000.0 (2000-01-01T12:00 UTC), and the
+//! events of a day in hours since its local midnight.
+
+/// Elevation of the centre of the sun at sunrise and sunset, in degrees,
+/// accounting for its radius and for refraction.
+pub const SUNRISE_ELEVATION: f64 = -0.833;
+/// Elevation of the sun at the end of civil, nautical and astronomical twilight.
+pub const CIVIL_TWILIGHT: f64 = -6.0;
+pub const NAUTICAL_TWILIGHT: f64 = -12.0;
+pub const ASTRONOMICAL_TWILIGHT: f64 = -18.0;
+/// Elevations of the sun bounding the blue hour and the golden hour, for photographers.
+pub const BLUE_HOUR: (f64, f64) = (-6.0, -4.0);
+pub const GOLDEN_HOUR: (f64, f64) = (-4.0, 6.0);
+/// Samples of the elevation in each hour.
+const SAMPLES_PER_HOUR: usize = 4;
+/// Days from the Unix epoch to J2000.0, 2000-01-01T12:00 UTC.
+const J2000_UNIX_DAYS: f64 = 10957.5;
+/// Hours between two samples when looking for events, short enough not to miss any.
+const SEARCH_STEP: f64 = 1.0 / 6.0;
+/// Length of a sidereal day, in solar days.
+const SIDEREAL_DAY: f64 = 0.997_269_6;
+
+/// Days since 1970-01-01 of a date of the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Days since J2000.0 of a local time like "2023-11-10T14:00", at `utc_offset_seconds` from UTC.
+pub fn days_since_j2000(local_time: &str, utc_offset_seconds: f64) -> Option<f64> {
+    let (date, time) = local_time.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let mut time = time.splitn(3, ':').map(str::parse::<f64>);
+    let hour = time.next()?.ok()?;
+    let minute = time.next().and_then(Result::ok).unwrap_or(0.0);
+    let seconds = hour * 3600.0 + minute * 60.0 - utc_offset_seconds;
+    Some(days_from_civil(year, month, day) as f64 + seconds / 86400.0 - J2000_UNIX_DAYS)
+}
+
+/// Local midnight of a date like "2023-11-10", in days since J2000.0.
+pub fn midnight(date: &str, utc_offset_seconds: f64) -> Option<f64> {
+    days_since_j2000(&format!("{date}T00:00"), utc_offset_seconds)
+}
+
+/// Hours since midnight, like "06:53".
+pub fn format_hour(hours: f64) -> String {
+    let minutes = (hours * 60.0).round() as i64;
+    format!(
+        "{:02}:{:02}",
+        minutes.div_euclid(60),
+        minutes.rem_euclid(60)
+    )
+}
+
+/// Obliquity of the ecliptic, in radians, `days` after J2000.0.
+pub fn obliquity(days: f64) -> f64 {
+    (23.439 - 0.000_000_4 * days).to_radians()
+}
+
+/// Ecliptic longitude of the sun, in degrees, `days` after J2000.0.
+pub fn ecliptic_longitude(days: f64) -> f64 {
+    let mean_longitude = 280.460 + 0.985_647_4 * days;
+    let mean_anomaly = (357.528 + 0.985_600_3 * days).to_radians();
+    mean_longitude + 1.915 * mean_anomaly.sin() + 0.020 * (2.0 * mean_anomaly).sin()
+}
+
+/// Declination and right ascension of the sun, in radians, `days` after J2000.0.
+pub fn equatorial(days: f64) -> (f64, f64) {
+    let longitude = ecliptic_longitude(days).to_radians();
+    let obliquity = obliquity(days);
+    let declination = (obliquity.sin() * longitude.sin()).asin();
+    let right_ascension = (obliquity.cos() * longitude.sin()).atan2(longitude.cos());
+    (declination, right_ascension)
+}
+
+/// Local hour angle, in radians, of an object at `right_ascension`, seen from `longitude`.
+pub fn hour_angle(days: f64, longitude: f64, right_ascension: f64) -> f64 {
+    let sidereal_degrees = 280.460_618_37 + 360.985_647_366_29 * days;
+    (sidereal_degrees + longitude).to_radians() - right_ascension
+}
+
+/// Azimuth, clockwise from north, and elevation, in degrees, of an object at
+/// (declination, right ascension) in radians.
+pub fn horizontal(
+    latitude: f64,
+    longitude: f64,
+    days: f64,
+    (declination, right_ascension): (f64, f64),
+) -> (f64, f64) {
+    let hour_angle = hour_angle(days, longitude, right_ascension);
+    let latitude = latitude.to_radians();
+    let elevation = (latitude.sin() * declination.sin()
+        + latitude.cos() * declination.cos() * hour_angle.cos())
+    .asin();
+    let azimuth = hour_angle
+        .sin()
+        .atan2(hour_angle.cos() * latitude.sin() - declination.tan() * latitude.cos());
+    (
+        (azimuth.to_degrees() + 180.0).rem_euclid(360.0),
+        elevation.to_degrees(),
+    )
+}
+
+/// Azimuth, clockwise from north, and elevation of the sun, in degrees, `days` after J2000.0.
+pub fn position(latitude: f64, longitude: f64, days: f64) -> (f64, f64) {
+    horizontal(latitude, longitude, days, equatorial(days))
+}
+
+/// Elevation of the sun above the horizon, in degrees, `days` after J2000.0.
+pub fn elevation(latitude: f64, longitude: f64, days: f64) -> f64 {
+    position(latitude, longitude, days).1
+}
+
+/// Times where `altitude` crosses `target` during `hours` hours from `start`,
+/// in hours since `start`, with whether it is rising.
+pub fn crossings(
+    altitude: impl Fn(f64) -> f64,
+    target: f64,
+    start: f64,
+    hours: f64,
+) -> Vec<(f64, bool)> {
+    let above = |hour: f64| altitude(start + hour / 24.0) >= target;
+    let steps = (hours / SEARCH_STEP).ceil() as usize;
+    let mut found = Vec::new();
+    let mut before = above(0.0);
+    for step in 1..=steps {
+        let (mut low, mut high) = (
+            (step - 1) as f64 * SEARCH_STEP,
+            (step as f64 * SEARCH_STEP).min(hours),
+        );
+        let after = above(high);
+        if after != before {
+            // Bisection, to well under a second
+            for _ in 0..16 {
+                let middle = (low + high) / 2.0;
+                if above(middle) == after {
+                    high = middle;
+                } else {
+                    low = middle;
+                }
+            }
+            found.push(((low + high) / 2.0, after));
+        }
+        before = after;
+    }
+    found
+}
+
+/// Ranges of hours of a day, from `start`, with `altitude` between `low` and `high`.
+pub fn periods(
+    altitude: impl Fn(f64) -> f64,
+    (low, high): (f64, f64),
+    start: f64,
+) -> Vec<(f64, f64)> {
+    let mut events = crossings(&altitude, low, start, 24.0);
+    events.extend(crossings(&altitude, high, start, 24.0));
+    events.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut inside = (low..=high).contains(&altitude(start));
+    let mut from = inside.then_some(0.0);
+    let mut periods = Vec::new();
+    // Each crossing of either bound enters or leaves the range
+    for (hour, _) in events {
+        inside = !inside;
+        match from.take() {
+            Some(from) => periods.push((from, hour)),
+            None => from = Some(hour),
+        }
+    }
+    if let Some(from) = from {
+        periods.push((from, 24.0));
+    }
+    periods
+}
+
+/// Events of the sun during a day, in hours since local midnight.
+///
+/// Events which do not happen that day, like sunrise during polar night, are `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SunDay {
+    pub sunrise: Option<f64>,
+    pub sunset: Option<f64>,
+    /// When the sun is highest, due south or north.
+    pub noon: f64,
+    pub noon_elevation: f64,
+    /// Hours with the sun above the horizon, 0 during polar night and 24 during polar day.
+    pub day_length: f64,
+    /// Start and end of twilight, with the sun up to 6°, 12° and 18° below the horizon.
+    pub civil_twilight: (Option<f64>, Option<f64>),
+    pub nautical_twilight: (Option<f64>, Option<f64>),
+    pub astronomical_twilight: (Option<f64>, Option<f64>),
+    pub blue_hours: Vec<(f64, f64)>,
+    pub golden_hours: Vec<(f64, f64)>,
+}
+
+impl SunDay {
+    /// Events of a date like "2023-11-10", at a place at `utc_offset_seconds` from UTC.
+    pub fn new(latitude: f64, longitude: f64, date: &str, utc_offset_seconds: f64) -> Option<Self> {
+        let start = midnight(date, utc_offset_seconds)?;
+        let altitude = |days: f64| elevation(latitude, longitude, days);
+        let rise_and_set = |target: f64| {
+            let events = crossings(altitude, target, start, 24.0);
+            let first = |rising: bool| {
+                events
+                    .iter()
+                    .find(|event| event.1 == rising)
+                    .map(|event| event.0)
+            };
+            (first(true), first(false))
+        };
+        let (sunrise, sunset) = rise_and_set(SUNRISE_ELEVATION);
+        // Twilight begins when the sun rises above a depression, and ends when it sets below it
+        let twilight = |depression: f64| rise_and_set(depression);
+        // The sun crosses the meridian when its hour angle is zero
+        let mut noon = start + 0.5;
+        for _ in 0..3 {
+            let hour_angle = hour_angle(noon, longitude, equatorial(noon).1);
+            let hour_angle = (hour_angle + std::f64::consts::PI).rem_euclid(std::f64::consts::TAU)
+                - std::f64::consts::PI;
+            noon -= hour_angle / std::f64::consts::TAU * SIDEREAL_DAY;
+        }
+        Some(SunDay {
+            sunrise,
+            sunset,
+            noon: (noon - start) * 24.0,
+            noon_elevation: altitude(noon),
+            day_length: periods(altitude, (SUNRISE_ELEVATION, 90.0), start)
+                .iter()
+                .map(|(from, to)| to - from)
+                .fold(0.0, |length, hours| length + hours),
+            civil_twilight: twilight(CIVIL_TWILIGHT),
+            nautical_twilight: twilight(NAUTICAL_TWILIGHT),
+            astronomical_twilight: twilight(ASTRONOMICAL_TWILIGHT),
+            blue_hours: periods(altitude, BLUE_HOUR, start),
+            golden_hours: periods(altitude, GOLDEN_HOUR, start),
+        })
+    }
+}
+
+/// Elevation of the sun over the hours of a forecast.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SunTrack {
+    /// Elevations, in degrees, `SAMPLES_PER_HOUR` of them per hour from the first hour.
+    elevations: Vec<f64>,
+}
+
+impl SunTrack {
+    /// Track over `hours` hours from `start`, in days since J2000.0.
+    pub fn new(latitude: f64, longitude: f64, start: f64, hours: usize) -> Self {
+        let elevations = (0..=hours * SAMPLES_PER_HOUR)
+            .map(|i| {
+                let hour = i as f64 / SAMPLES_PER_HOUR as f64;
+                elevation(latitude, longitude, start + hour / 24.0)
+            })
+            .collect();
+        SunTrack { elevations }
+    }
+
+    /// Elevations with the hours they are at, since the first hour.
+    pub fn points(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.elevations
+            .iter()
+            .enumerate()
+            .map(|(i, &elevation)| (i as f64 / SAMPLES_PER_HOUR as f64, elevation))
+    }
+
+    /// Ranges of hours with the sun below the horizon.
+    ///
+    /// There is none during polar day, and a single one during polar night.
+    pub fn nights(&self) -> Vec<(f64, f64)> {
+        let mut nights = Vec::new();
+        let mut start = None;
+        let mut previous: Option<(f64, f64)> = None;
+        for (hour, elevation) in self.points() {
+            let night = elevation < SUNRISE_ELEVATION;
+            // Where the sun crosses the horizon, between two samples
+            let crossing = || match previous {
+                Some((before, e)) => {
+                    before + (SUNRISE_ELEVATION - e) / (elevation - e) * (hour - before)
+                }
+                None => hour,
+            };
+            match (night, start) {
+                (true, None) => start = Some(crossing()),
+                (false, Some(s)) => {
+                    nights.push((s, crossing()));
+                    start = None;
+                }
+                _ => {}
+            }
+            previous = Some((hour, elevation));
+        }
+        if let (Some(s), Some((end, _))) = (start, previous) {
+            nights.push((s, end));
+        }
+        nights
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_days() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 1, 1), 10957);
+        assert_eq!(days_from_civil(2024, 2, 29), 19782);
+        assert_eq!(days_since_j2000("2000-01-01T12:00", 0.0), Some(0.0));
+        assert_eq!(days_since_j2000("2000-01-01T14:00", 7200.0), Some(0.0));
+        assert_eq!(days_since_j2000("2000-01-01", 0.0), None);
+    }
+
+    #[test]
+    fn noon_elevation() {
+        // Vienna at the June solstice: 90° - 48.2° + 23.44°
+        let start = days_since_j2000("2023-06-21T00:00", 7200.0).unwrap();
+        let track = SunTrack::new(48.2, 16.37, start, 24);
+        let highest = track.points().map(|(_, e)| e).fold(f64::MIN, f64::max);
+        assert!((highest - 65.24).abs() < 0.1, "{highest}");
+    }
+
+    #[test]
+    fn sunrise_and_sunset() {
+        // Vienna on 2023-11-10: sunrise at 06:53 and sunset at 16:24 (CET)
+        let start = days_since_j2000("2023-11-10T00:00", 3600.0).unwrap();
+        let nights = SunTrack::new(48.21, 16.37, start, 24).nights();
+        assert_eq!(nights.len(), 2);
+        assert!(
+            (nights[0].1 - (6.0 + 53.0 / 60.0)).abs() < 0.05,
+            "{nights:?}"
+        );
+        assert!(
+            (nights[1].0 - (16.0 + 24.0 / 60.0)).abs() < 0.05,
+            "{nights:?}"
+        );
+        assert_eq!(nights[1].1, 24.0);
+    }
+
+    /// Within a minute and a half of `expected`, like "05:43", almanacs rounding
+    /// to the minute.
+    fn assert_near(hours: Option<f64>, expected: &str) {
+        let hours = hours.expect(expected);
+        let (hour, minute) = expected.split_once(':').unwrap();
+        let expected = hour.parse::<f64>().unwrap() + minute.parse::<f64>().unwrap() / 60.0;
+        assert!(
+            (hours - expected).abs() <= 1.5 / 60.0,
+            "{} instead of {}",
+            format_hour(hours),
+            format_hour(expected)
+        );
+    }
+
+    #[test]
+    fn almanac() {
+        // Solar noon at Greenwich, with the equation of time at its extremes
+        let greenwich = SunDay::new(51.4769, 0.0, "2023-11-03", 0.0).unwrap();
+        assert!(
+            (greenwich.noon * 3600.0 - 42214.0).abs() < 5.0,
+            "{}",
+            greenwich.noon
+        );
+        let greenwich = SunDay::new(51.4769, 0.0, "2024-02-11", 0.0).unwrap();
+        assert!(
+            (greenwich.noon * 3600.0 - 44053.0).abs() < 5.0,
+            "{}",
+            greenwich.noon
+        );
+        // Washington, DC, on 2024-06-20 (EDT), from the US Naval Observatory
+        let washington = SunDay::new(38.8895, -77.0353, "2024-06-20", -14400.0).unwrap();
+        assert_near(washington.civil_twilight.0, "05:10");
+        assert_near(washington.sunrise, "05:43");
+        assert_near(Some(washington.noon), "13:10");
+        assert_near(washington.sunset, "20:37");
+        assert_near(washington.civil_twilight.1, "21:10");
+        assert!((washington.noon_elevation - 74.55).abs() < 0.05);
+        assert!((washington.day_length - (20.0 + 37.0 / 60.0 - 5.0 - 43.0 / 60.0)).abs() < 0.03);
+        // Sydney on 2024-01-01 (AEDT), in the southern summer
+        let sydney = SunDay::new(-33.87, 151.21, "2024-01-01", 39600.0).unwrap();
+        assert_near(sydney.sunrise, "05:47");
+        assert_near(sydney.sunset, "20:09");
+        // The sun is due south at noon north of the tropics, and due north south of them
+        let start = midnight("2024-01-01", 39600.0).unwrap();
+        let (azimuth, _) = position(-33.87, 151.21, start + sydney.noon / 24.0);
+        assert!(!(0.01..=359.99).contains(&azimuth), "{azimuth}");
+    }
+
+    #[test]
+    fn polar_events() {
+        // Tromsø in polar night: no sunrise, but a few hours of twilight
+        let winter = SunDay::new(69.65, 18.96, "2023-12-15", 3600.0).unwrap();
+        assert_eq!(
+            (winter.sunrise, winter.sunset, winter.day_length),
+            (None, None, 0.0)
+        );
+        assert!(winter.civil_twilight.0.is_some() && winter.noon_elevation < 0.0);
+        assert_eq!(winter.golden_hours.len(), 1);
+        // And during the midnight sun, no sunset and no twilight
+        let summer = SunDay::new(69.65, 18.96, "2023-06-21", 7200.0).unwrap();
+        assert_eq!(
+            (summer.sunrise, summer.sunset, summer.day_length),
+            (None, None, 24.0)
+        );
+        assert_eq!(summer.civil_twilight, (None, None));
+        assert_eq!(summer.blue_hours, []);
+    }
+
+    #[test]
+    fn polar_day_and_night() {
+        // Tromsø, 69.65° N
+        let summer = days_since_j2000("2023-06-21T00:00", 7200.0).unwrap();
+        assert_eq!(SunTrack::new(69.65, 18.96, summer, 48).nights(), []);
+        let winter = days_since_j2000("2023-12-21T00:00", 3600.0).unwrap();
+        assert_eq!(
+            SunTrack::new(69.65, 18.96, winter, 48).nights(),
+            [(0.0, 48.0)]
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Hour-by-hour table of the forecast, an alternative to the charts.
+
+use std::cmp::Ordering;
+
+use leptos::*;
+
+use crate::{
+    data::wind_arrow,
+    storage,
+    units::Units,
+    weather_code::{WeatherCode, WeatherIcon},
+};
+
+const STORAGE_KEY: &str = "weather.table_columns";
+
+/// Forecast for one hour.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    /// Local time, like "2023-11-10T14:00".
+    pub time: String,
+    pub weather_code: WeatherCode,
+    pub is_day: bool,
+    pub temperature: f64,
+    pub apparent_temperature: f64,
+    pub precipitation: f64,
+    pub precipitation_probability: f64,
+    pub wind_speed: f64,
+    pub wind_direction: f64,
+    pub uv_index: f64,
+}
+
+impl Row {
+    pub fn day(&self) -> &str {
+        self.time.split_once('T').map_or(&self.time, |(day, _)| day)
+    }
+
+    fn hour(&self) -> &str {
+        self.time
+            .split_once('T')
+            .map_or(&self.time, |(_, hour)| hour)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Column {
+    Time,
+    Symbol,
+    Temperature,
+    FeelsLike,
+    Precipitation,
+    PrecipitationProbability,
+    Wind,
+    Uv,
+}
+
+impl Column {
+    pub const ALL: [Self; 8] = [
+        Self::Time,
+        Self::Symbol,
+        Self::Temperature,
+        Self::FeelsLike,
+        Self::Precipitation,
+        Self::PrecipitationProbability,
+        Self::Wind,
+        Self::Uv,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Time => "Time",
+            Self::Symbol => "Weather",
+            Self::Temperature => "Temperature",
+            Self::FeelsLike => "Feels like",
+            Self::Precipitation => "Precipitation",
+            Self::PrecipitationProbability => "Probability",
+            Self::Wind => "Wind",
+            Self::Uv => "UV",
+        }
+    }
+
+    fn compare(self, a: &Row, b: &Row) -> Ordering {
+        let number = |row: &Row| match self {
+            Self::Time => 0.0,
+            Self::Symbol => f64::from(u8::from(row.weather_code)),
+            Self::Temperature => row.temperature,
+            Self::FeelsLike => row.apparent_temperature,
+            Self::Precipitation => row.precipitation,
+            Self::PrecipitationProbability => row.precipitation_probability,
+            Self::Wind => row.wind_speed,
+            Self::Uv => row.uv_index,
+        };
+        match self {
+            Self::Time => a.time.cmp(&b.time),
+            _ => number(a).total_cmp(&number(b)),
+        }
+    }
+
+    fn cell(self, row: &Row, units: Units) -> View {
+        match self {
+            Self::Time => view! { <th scope="row">{row.hour().to_string()}</th> }.into_view(),
+            Self::Symbol => view! {
+                <td>
+                    <svg viewBox="0 0 10 10" xmlns="http://www.w3.org/2000/svg" width="2em" height="2em">
+                        <WeatherIcon code=row.weather_code is_day=row.is_day x=0.0 y=0.0 size=10.0/>
+                    </svg>
+                </td>
+            }
+            .into_view(),
+            Self::Temperature => {
+                view! { <td>{display(row.temperature)} {units.temperature.symbol()}</td> }.into_view()
+            }
+            Self::FeelsLike => {
+                view! { <td>{display(row.apparent_temperature)} {units.temperature.symbol()}</td> }
+                    .into_view()
+            }
+            Self::Precipitation => {
+                view! { <td>{display(row.precipitation)} " " {units.precipitation.symbol()}</td> }
+                    .into_view()
+            }
+            Self::PrecipitationProbability => {
+                view! { <td>{display(row.precipitation_probability)} "%"</td> }.into_view()
+            }
+            Self::Wind => view! {
+                <td>{wind_arrow(row.wind_direction)} " " {display(row.wind_speed)} " " {units.wind_speed.symbol()}</td>
+            }
+            .into_view(),
+            Self::Uv => view! { <td>{display(row.uv_index)}</td> }.into_view(),
+        }
+    }
+}
+
+/// A value for people to read, with a dash if it is missing.
+pub fn display(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "–".to_string()
+    }
+}
+
+/// Sort rows by `column`, keeping the rows of each day together and days in order.
+pub fn sort(rows: &mut [Row], column: Column, ascending: bool) {
+    rows.sort_by(|a, b| {
+        let ordering = column.compare(a, b);
+        a.day().cmp(b.day()).then(if ascending {
+            ordering
+        } else {
+            ordering.reverse()
+        })
+    });
+}
+
+/// Rows split into consecutive days.
+fn days(rows: &[Row]) -> Vec<&[Row]> {
+    rows.chunk_by(|a, b| a.day() == b.day()).collect()
+}
+
+#[component]
+pub fn HourlyTable(rows: Vec<Row>, units: Units) -> impl IntoView {
+    let (columns, set_columns) =
+        create_signal(storage::get(STORAGE_KEY).unwrap_or(Column::ALL.to_vec()));
+    create_effect(move |_| columns.with(|columns| storage::set(STORAGE_KEY, columns)));
+    let (sorting, set_sorting) = create_signal((Column::Time, true));
+    let sorted_rows = move || {
+        let (column, ascending) = sorting.get();
+        let mut rows = rows.clone();
+        sort(&mut rows, column, ascending);
+        rows
+    };
+    let sort_by = move |column| {
+        set_sorting.update(|(sorted_by, ascending)| {
+            if *sorted_by == column {
+                *ascending = !*ascending;
+            } else {
+                *sorted_by = column;
+                *ascending = true;
+            }
+        })
+    };
+
+    view! {
+        <details class="table_columns">
+            <summary>"Columns"</summary>
+            {Column::ALL
+                .into_iter()
+                .map(|column| view! {
+                    <label>
+                        <input
+                            type="checkbox"
+                            prop:checked=move || columns.with(|columns| columns.contains(&column))
+                            on:change=move |ev| {
+                                let checked = event_target_checked(&ev);
+                                set_columns.update(|columns| {
+                                    columns.retain(|c| *c != column);
+                                    if checked {
+                                        columns.push(column);
+                                        columns.sort_by_key(|c| Column::ALL.iter().position(|all| all == c));
+                                    }
+                                });
+                            }
+                        />
+                        {column.label()}
+                    </label>
+                })
+                .collect_view()
+            }
+        </details>
+        <div class="table_container">
+            <table class="hourly_table">
+                <thead>
+                    <tr>
+                        {move || columns.get()
+                            .into_iter()
+                            .map(|column| {
+                                let sort_order = move || match sorting.get() {
+                                    (sorted_by, true) if sorted_by == column => "ascending",
+                                    (sorted_by, false) if sorted_by == column => "descending",
+                                    _ => "none",
+                                };
+                                view! {
+                                    <th scope="col" aria-sort=sort_order>
+                                        <button type="button" on:click=move |_| sort_by(column)>
+                                            {column.label()}
+                                            {move || match sort_order() {
+                                                "ascending" => " ▲",
+                                                "descending" => " ▼",
+                                                _ => "",
+                                            }}
+                                        </button>
+                                    </th>
+                                }
+                            })
+                            .collect_view()
+                        }
+                    </tr>
+                </thead>
+                {move || {
+                    let rows = sorted_rows();
+                    let columns = columns.get();
+                    days(&rows)
+                        .into_iter()
+                        .map(|day| view! {
+                            <tbody>
+                                <tr class="table_day">
+                                    <th scope="rowgroup" colspan=columns.len()>{day[0].day().to_string()}</th>
+                                </tr>
+                                {day.iter()
+                                    .map(|row| view! {
+                                        <tr>
+                                            {columns.iter().map(|column| column.cell(row, units)).collect_view()}
+                                        </tr>
+                                    })
+                                    .collect_view()
+                                }
+                            </tbody>
+                        })
+                        .collect_view()
+                }}
+            </table>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(time: &str, precipitation: f64) -> Row {
+        Row {
+            time: time.to_string(),
+            weather_code: WeatherCode::ClearSky,
+            is_day: true,
+            temperature: 0.0,
+            apparent_temperature: 0.0,
+            precipitation,
+            precipitation_probability: 0.0,
+            wind_speed: 0.0,
+            wind_direction: 0.0,
+            uv_index: 0.0,
+        }
+    }
+
+    #[test]
+    fn sorting_keeps_days_together() {
+        let mut rows = vec![
+            row("2023-11-10T00:00", 0.5),
+            row("2023-11-10T01:00", 2.0),
+            row("2023-11-11T00:00", 3.0),
+            row("2023-11-11T01:00", 1.0),
+        ];
+        sort(&mut rows, Column::Precipitation, false);
+        let times: Vec<_> = rows.iter().map(|row| row.time.as_str()).collect();
+        assert_eq!(
+            times,
+            [
+                "2023-11-10T01:00",
+                "2023-11-10T00:00",
+                "2023-11-11T00:00",
+                "2023-11-11T01:00"
+            ]
+        );
+        assert_eq!(days(&rows).len(), 2);
+        sort(&mut rows, Column::Time, true);
+        assert_eq!(rows[0].time, "2023-11-10T00:00");
+    }
+}
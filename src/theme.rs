@@ -0,0 +1,380 @@
+//! Colour themes: light, dark and high contrast.
+//!
+//! The page follows the system preferences (`prefers-color-scheme` and
+//! `prefers-contrast`) unless the user picks a theme. Page colours are CSS
+//! custom properties selected by a `data-theme` attribute, while charts get
+//! their colours from the [`Palette`] provided as context.
+//!
+//! Colour scales can also be switched to perceptually uniform ones, readable
+//! with any colour vision deficiency. Charts do not rely on colour alone though:
+//! thresholds are also marked by shapes, dashes and hatching.
+
+use leptos::*;
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "weather.theme";
+const SCALE_STORAGE_KEY: &str = "weather.color_scale";
+
+/// A colour, as red, green and blue.
+pub type Rgb = (u8, u8, u8);
+
+/// Viridis, sampled every eighth of the way.
+const VIRIDIS: [Rgb; 9] = [
+    (68, 1, 84),
+    (71, 45, 123),
+    (59, 82, 139),
+    (44, 114, 142),
+    (33, 145, 140),
+    (40, 174, 128),
+    (94, 201, 98),
+    (173, 220, 48),
+    (253, 231, 37),
+];
+/// Cividis, sampled every eighth of the way.
+const CIVIDIS: [Rgb; 9] = [
+    (0, 34, 78),
+    (35, 62, 108),
+    (83, 89, 112),
+    (112, 113, 115),
+    (139, 138, 118),
+    (168, 162, 116),
+    (201, 188, 107),
+    (234, 216, 83),
+    (254, 232, 56),
+];
+
+/// Colour at a fraction `t` of a scale given by evenly spaced stops.
+pub fn scale_color(stops: &[Rgb], t: f64) -> String {
+    let Some(&last) = stops.last() else {
+        return "transparent".to_string();
+    };
+    let position = t.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+    let i = (position.floor() as usize).min(stops.len() - 1);
+    let (from, to) = (stops[i], stops.get(i + 1).copied().unwrap_or(last));
+    let mix =
+        |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * position.fract()).round();
+    format!(
+        "rgb({}, {}, {})",
+        mix(from.0, to.0),
+        mix(from.1, to.1),
+        mix(from.2, to.2)
+    )
+}
+
+/// Colour scale of the temperature and the UV index.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ColorScale {
+    /// The colours of the theme.
+    #[default]
+    Standard,
+    Viridis,
+    Cividis,
+}
+
+impl ColorScale {
+    pub const ALL: [Self; 3] = [Self::Standard, Self::Viridis, Self::Cividis];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ColorScale::Standard => "Standard colours",
+            ColorScale::Viridis => "Viridis (colour-blind safe)",
+            ColorScale::Cividis => "Cividis (colour-blind safe)",
+        }
+    }
+
+    pub fn load() -> Self {
+        storage::get(SCALE_STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn store(&self) {
+        storage::set(SCALE_STORAGE_KEY, self);
+    }
+
+    /// Stops of the scale, if it replaces the colours of the theme.
+    fn stops(self) -> Option<&'static [Rgb]> {
+        match self {
+            ColorScale::Standard => None,
+            ColorScale::Viridis => Some(&VIRIDIS),
+            ColorScale::Cividis => Some(&CIVIDIS),
+        }
+    }
+}
+
+/// Theme chosen by the user.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ThemeChoice {
+    /// Follow the preferences of the system.
+    #[default]
+    System,
+    Light,
+    Dark,
+    HighContrast,
+}
+
+impl ThemeChoice {
+    pub const ALL: [Self; 4] = [Self::System, Self::Light, Self::Dark, Self::HighContrast];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeChoice::System => "System",
+            ThemeChoice::Light => "Light",
+            ThemeChoice::Dark => "Dark",
+            ThemeChoice::HighContrast => "High contrast",
+        }
+    }
+
+    /// Load the preference from the browser storage, or default to the system's.
+    pub fn load() -> Self {
+        storage::get(STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn store(&self) {
+        storage::set(STORAGE_KEY, self);
+    }
+
+    /// Theme shown, given the preferences of the system.
+    pub fn resolve(self, prefers_dark: bool, prefers_contrast: bool) -> Theme {
+        match self {
+            ThemeChoice::System if prefers_contrast => Theme::HighContrast,
+            ThemeChoice::System if prefers_dark => Theme::Dark,
+            ThemeChoice::System | ThemeChoice::Light => Theme::Light,
+            ThemeChoice::Dark => Theme::Dark,
+            ThemeChoice::HighContrast => Theme::HighContrast,
+        }
+    }
+}
+
+/// Theme shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Light,
+    Dark,
+    HighContrast,
+}
+
+impl Theme {
+    /// Value of the `data-theme` attribute selecting the CSS of the theme.
+    fn css_name(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::HighContrast => "high_contrast",
+        }
+    }
+
+    pub fn palette(self, scale: ColorScale) -> Palette {
+        let mut palette = match self {
+            Theme::Light => Palette::LIGHT,
+            Theme::Dark => Palette::DARK,
+            Theme::HighContrast => Palette::HIGH_CONTRAST,
+        };
+        if let Some(stops) = scale.stops() {
+            // The darkest colours would not stand out of a dark background
+            let stops = if self == Theme::Dark {
+                &stops[2..]
+            } else {
+                stops
+            };
+            palette.temperature = stops;
+            palette.uv = stops;
+        }
+        palette
+    }
+}
+
+/// Colours of the charts, as CSS colours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub text: &'static str,
+    pub grid: &'static str,
+    /// Scale of the temperatures, from the coldest to the warmest.
+    pub temperature: &'static [Rgb],
+    pub rain: &'static str,
+    /// Shading of the nights, over the background.
+    pub night: &'static str,
+    pub sun: &'static str,
+    /// Scale of the UV index, from the lowest to the highest.
+    pub uv: &'static [Rgb],
+    /// Colours of the weather symbols. Snow is filled with `snow` and outlined with
+    /// `cloud`, so that it shows on any background.
+    pub moon: &'static str,
+    pub cloud: &'static str,
+    pub snow: &'static str,
+}
+
+impl Palette {
+    pub const LIGHT: Self = Palette {
+        text: "rgb(86, 97, 108)",
+        grid: "rgb(200, 204, 210)",
+        temperature: &[(0, 90, 200), (110, 170, 230), (240, 150, 60), (200, 16, 46)],
+        rain: "rgb(78, 104, 129)",
+        night: "rgba(78, 104, 129, 0.08)",
+        sun: "rgb(230, 170, 0)",
+        // Through the hues between green and magenta
+        uv: &[(0, 255, 0), (0, 255, 255), (0, 0, 255), (255, 0, 255)],
+        moon: "rgb(150, 160, 180)",
+        cloud: "rgb(170, 180, 192)",
+        snow: "rgb(190, 215, 240)",
+    };
+    pub const DARK: Self = Palette {
+        text: "rgb(200, 208, 216)",
+        grid: "rgb(70, 78, 88)",
+        temperature: &[
+            (80, 140, 255),
+            (130, 190, 245),
+            (255, 180, 90),
+            (255, 99, 99),
+        ],
+        rain: "rgb(120, 160, 200)",
+        night: "rgba(0, 0, 0, 0.3)",
+        sun: "rgb(255, 200, 40)",
+        uv: &[
+            (60, 200, 90),
+            (60, 190, 200),
+            (80, 110, 240),
+            (230, 80, 230),
+        ],
+        moon: "rgb(200, 208, 220)",
+        cloud: "rgb(150, 160, 172)",
+        snow: "rgb(255, 255, 255)",
+    };
+    pub const HIGH_CONTRAST: Self = Palette {
+        text: "rgb(0, 0, 0)",
+        grid: "rgb(90, 90, 90)",
+        temperature: &[(0, 0, 140), (0, 90, 200), (190, 80, 0), (170, 0, 0)],
+        rain: "rgb(0, 60, 130)",
+        night: "rgba(0, 0, 0, 0.15)",
+        sun: "rgb(150, 100, 0)",
+        uv: &[(0, 130, 0), (0, 120, 130), (0, 0, 160), (160, 0, 160)],
+        moon: "rgb(60, 70, 90)",
+        cloud: "rgb(90, 90, 90)",
+        snow: "rgb(255, 255, 255)",
+    };
+}
+
+/// Whether a media query matches, following its changes.
+fn media_query(query: &str) -> ReadSignal<bool> {
+    let list = window().match_media(query).ok().flatten();
+    let (matches, set_matches) = create_signal(list.as_ref().is_some_and(|list| list.matches()));
+    if let Some(list) = list {
+        let on_change = Closure::<dyn Fn(web_sys::MediaQueryListEvent)>::new(
+            move |ev: web_sys::MediaQueryListEvent| set_matches.set(ev.matches()),
+        );
+        if list
+            .add_event_listener_with_callback("change", on_change.as_ref().unchecked_ref())
+            .is_ok()
+        {
+            // Listens for as long as the page is open
+            on_change.forget();
+        }
+    }
+    matches
+}
+
+/// Apply the chosen theme to the page, and provide its palette to the charts.
+pub fn provide_theme(choice: ReadSignal<ThemeChoice>, scale: ReadSignal<ColorScale>) {
+    let prefers_dark = media_query("(prefers-color-scheme: dark)");
+    let prefers_contrast = media_query("(prefers-contrast: more)");
+    let theme = create_memo(move |_| {
+        choice
+            .get()
+            .resolve(prefers_dark.get(), prefers_contrast.get())
+    });
+    create_effect(move |_| {
+        if let Some(root) = document().document_element() {
+            if root
+                .set_attribute("data-theme", theme.get().css_name())
+                .is_err()
+            {
+                log::warn!("failed to apply the theme");
+            }
+        }
+    });
+    provide_context(Signal::derive(move || theme.get().palette(scale.get())));
+}
+
+/// Palette of the current theme, or the light one outside of the app.
+pub fn use_palette() -> Signal<Palette> {
+    use_context().unwrap_or_else(|| Signal::derive(|| Palette::LIGHT))
+}
+
+#[component]
+pub fn ThemeSelector(
+    choice: ReadSignal<ThemeChoice>,
+    set_choice: WriteSignal<ThemeChoice>,
+    scale: ReadSignal<ColorScale>,
+    set_scale: WriteSignal<ColorScale>,
+) -> impl IntoView {
+    create_effect(move |_| choice.with(ThemeChoice::store));
+    create_effect(move |_| scale.with(ColorScale::store));
+
+    view! {
+        <fieldset>
+            <legend>"Theme 🎨"</legend>
+            <select
+                aria-label="Theme"
+                on:change=move |ev| {
+                    let choice = event_target_value(&ev)
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| ThemeChoice::ALL.get(i).copied());
+                    if let Some(choice) = choice {
+                        set_choice.set(choice);
+                    }
+                }
+            >
+                {ThemeChoice::ALL.into_iter().enumerate().map(|(i, option)| view! {
+                    <option value=i prop:selected=move || choice.get() == option>{option.label()}</option>
+                }).collect_view()}
+            </select>
+            <select
+                aria-label="Colour scale"
+                on:change=move |ev| {
+                    let scale = event_target_value(&ev)
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| ColorScale::ALL.get(i).copied());
+                    if let Some(scale) = scale {
+                        set_scale.set(scale);
+                    }
+                }
+            >
+                {ColorScale::ALL.into_iter().enumerate().map(|(i, option)| view! {
+                    <option value=i prop:selected=move || scale.get() == option>{option.label()}</option>
+                }).collect_view()}
+            </select>
+        </fieldset>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_the_system_unless_chosen() {
+        assert_eq!(ThemeChoice::System.resolve(false, false), Theme::Light);
+        assert_eq!(ThemeChoice::System.resolve(true, false), Theme::Dark);
+        assert_eq!(ThemeChoice::System.resolve(true, true), Theme::HighContrast);
+        assert_eq!(ThemeChoice::Light.resolve(true, true), Theme::Light);
+        assert_eq!(ThemeChoice::Dark.resolve(false, false), Theme::Dark);
+    }
+
+    #[test]
+    fn scales() {
+        assert_eq!(scale_color(&VIRIDIS, 0.0), "rgb(68, 1, 84)");
+        assert_eq!(scale_color(&VIRIDIS, 1.0), "rgb(253, 231, 37)");
+        assert_eq!(scale_color(&VIRIDIS, 2.0), "rgb(253, 231, 37)");
+        assert_eq!(
+            scale_color(&[(0, 0, 0), (200, 100, 50)], 0.5),
+            "rgb(100, 50, 25)"
+        );
+        // The standard scale of the UV index goes through the hues, like HSL
+        assert_eq!(scale_color(Palette::LIGHT.uv, 0.5), "rgb(0, 128, 255)");
+        let palette = Theme::Dark.palette(ColorScale::Cividis);
+        assert_eq!(palette.temperature, &CIVIDIS[2..]);
+        assert_eq!(Theme::Light.palette(ColorScale::Standard), Palette::LIGHT);
+    }
+}
@@ -0,0 +1,167 @@
+//! Series moving from one forecast to the next.
+//!
+//! Each forecast builds a new meteogram. To not jump from the previous forecast to
+//! the new one, the meteogram starts from the values the previous one drew for the
+//! same hours, and moves them to the new values over a short animation.
+
+use std::collections::HashMap;
+
+use leptos::*;
+
+use crate::table::Row;
+
+/// Length of the move from the previous forecast, in milliseconds.
+const DURATION_MS: f64 = 400.0;
+
+/// Hours drawn by the last meteogram, for the next one to start from.
+#[derive(Clone, Copy)]
+struct Previous(StoredValue<Vec<Row>>);
+
+/// Let each meteogram start from the series of the previous forecast.
+pub fn provide_previous_rows() {
+    provide_context(Previous(store_value(Vec::new())));
+}
+
+/// Rows drawn over the animation from the previous forecast to `rows`, which
+/// become the previous ones of the next forecast.
+pub fn from_previous(rows: &[Row]) -> Memo<Vec<Row>> {
+    let from = use_context::<Previous>()
+        .map(|Previous(previous)| {
+            let from = previous.with_value(|previous| aligned(previous, rows));
+            previous.set_value(rows.to_vec());
+            from
+        })
+        .unwrap_or_default();
+    let moving = from.iter().any(Option::is_some) && !prefers_reduced_motion();
+    let progress = create_rw_signal(if moving { 0.0 } else { 1.0 });
+    if moving {
+        animate(progress, js_sys::Date::now());
+    }
+    let rows = rows.to_vec();
+    create_memo(move |_| {
+        let t = progress.get();
+        if t < 1.0 {
+            tween(&from, &rows, ease(t))
+        } else {
+            rows.clone()
+        }
+    })
+}
+
+/// Move `progress` from 0 to 1, one animation frame at a time.
+fn animate(progress: RwSignal<f64>, start: f64) {
+    request_animation_frame(move || {
+        let t = ((js_sys::Date::now() - start) / DURATION_MS).min(1.0);
+        // Stops once the meteogram is gone
+        if progress.try_set(t).is_none() && t < 1.0 {
+            animate(progress, start);
+        }
+    });
+}
+
+fn prefers_reduced_motion() -> bool {
+    window()
+        .match_media("(prefers-reduced-motion: reduce)")
+        .ok()
+        .flatten()
+        .is_some_and(|list| list.matches())
+}
+
+/// Slow at the start and the end.
+fn ease(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// The rows of `from` at the same times as `to`, if any.
+fn aligned(from: &[Row], to: &[Row]) -> Vec<Option<Row>> {
+    let from: HashMap<&str, &Row> = from.iter().map(|row| (row.time.as_str(), row)).collect();
+    to.iter()
+        .map(|row| from.get(row.time.as_str()).map(|&row| row.clone()))
+        .collect()
+}
+
+/// Rows of `to`, with their values a fraction `t` of the way from those of `from`.
+///
+/// Hours missing from either forecast, and values missing from either row, are
+/// those of `to` right away.
+fn tween(from: &[Option<Row>], to: &[Row], t: f64) -> Vec<Row> {
+    let mix = |a: f64, b: f64| {
+        if a.is_finite() && b.is_finite() {
+            a + (b - a) * t
+        } else {
+            b
+        }
+    };
+    to.iter()
+        .zip(from)
+        .map(|(to, from)| match from {
+            Some(from) => Row {
+                temperature: mix(from.temperature, to.temperature),
+                apparent_temperature: mix(from.apparent_temperature, to.apparent_temperature),
+                precipitation: mix(from.precipitation, to.precipitation),
+                precipitation_probability: mix(
+                    from.precipitation_probability,
+                    to.precipitation_probability,
+                ),
+                wind_speed: mix(from.wind_speed, to.wind_speed),
+                // The shortest way round
+                wind_direction: mix(
+                    from.wind_direction,
+                    from.wind_direction
+                        + (to.wind_direction - from.wind_direction + 180.0).rem_euclid(360.0)
+                        - 180.0,
+                )
+                .rem_euclid(360.0),
+                uv_index: mix(from.uv_index, to.uv_index),
+                ..to.clone()
+            },
+            None => to.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weather_code::WeatherCode;
+
+    fn row(time: &str, temperature: f64, wind_direction: f64) -> Row {
+        Row {
+            time: time.to_string(),
+            weather_code: WeatherCode::ClearSky,
+            is_day: true,
+            temperature,
+            apparent_temperature: temperature,
+            precipitation: 0.0,
+            precipitation_probability: 0.0,
+            wind_speed: 10.0,
+            wind_direction,
+            uv_index: 0.0,
+        }
+    }
+
+    #[test]
+    fn moves_between_forecasts() {
+        let from = [
+            row("2023-11-10T00:00", 0.0, 350.0),
+            row("2023-11-10T01:00", f64::NAN, 0.0),
+        ];
+        let to = [
+            row("2023-11-10T01:00", 4.0, 90.0),
+            row("2023-11-10T02:00", 6.0, 0.0),
+            row("2023-11-10T00:00", 2.0, 10.0),
+        ];
+        let from = aligned(&from, &to);
+        let halfway = tween(&from, &to, 0.5);
+        // Missing values and new hours do not move
+        assert_eq!(halfway[0].temperature, 4.0);
+        assert_eq!(halfway[1], to[1]);
+        assert_eq!(halfway[2].temperature, 1.0);
+        // The wind turns through north
+        assert_eq!(halfway[2].wind_direction, 0.0);
+        assert_eq!(halfway[0].wind_direction, 45.0);
+        assert_eq!(tween(&from, &to, 1.0)[2].wind_direction, 10.0);
+        assert_eq!(ease(0.0), 0.0);
+        assert_eq!(ease(1.0), 1.0);
+    }
+}
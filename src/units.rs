@@ -0,0 +1,314 @@
+//! Units in which forecasts are requested and shown.
+//!
+//! Open-Meteo converts temperature, wind speed and precipitation itself. Only the
+//! Beaufort scale is computed locally, from wind speeds requested in m/s.
+
+use leptos::*;
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "weather.units";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    pub const ALL: [Self; 2] = [Self::Celsius, Self::Fahrenheit];
+
+    fn api_name(self) -> &'static str {
+        match self {
+            Self::Celsius => "celsius",
+            Self::Fahrenheit => "fahrenheit",
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Celsius => "°C",
+            Self::Fahrenheit => "°F",
+        }
+    }
+
+    pub fn convert_celsius(self, celsius: f64) -> f64 {
+        match self {
+            Self::Celsius => celsius,
+            Self::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum WindSpeedUnit {
+    #[default]
+    KilometresPerHour,
+    MetresPerSecond,
+    MilesPerHour,
+    Knots,
+    Beaufort,
+}
+
+impl WindSpeedUnit {
+    pub const ALL: [Self; 5] = [
+        Self::KilometresPerHour,
+        Self::MetresPerSecond,
+        Self::MilesPerHour,
+        Self::Knots,
+        Self::Beaufort,
+    ];
+
+    fn api_name(self) -> &'static str {
+        match self {
+            Self::KilometresPerHour => "kmh",
+            Self::MetresPerSecond | Self::Beaufort => "ms",
+            Self::MilesPerHour => "mph",
+            Self::Knots => "kn",
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::KilometresPerHour => "km/h",
+            Self::MetresPerSecond => "m/s",
+            Self::MilesPerHour => "mph",
+            Self::Knots => "kn",
+            Self::Beaufort => "Bft",
+        }
+    }
+
+    /// Name in shared links, where m/s and Beaufort differ.
+    fn link_name(self) -> &'static str {
+        match self {
+            Self::Beaufort => "bft",
+            _ => self.api_name(),
+        }
+    }
+
+    /// Wind speed to show, from the value returned by the API.
+    pub fn convert_api_value(self, speed: f64) -> f64 {
+        match self {
+            Self::Beaufort => beaufort(speed) as f64,
+            _ => speed,
+        }
+    }
+}
+
+/// Beaufort number of a wind speed in m/s.
+pub fn beaufort(metres_per_second: f64) -> u8 {
+    // Upper limits of each force, in m/s
+    const LIMITS: [f64; 12] = [
+        0.5, 1.6, 3.4, 5.5, 8.0, 10.8, 13.9, 17.2, 20.8, 24.5, 28.5, 32.7,
+    ];
+    LIMITS
+        .iter()
+        .position(|&limit| metres_per_second < limit)
+        .unwrap_or(LIMITS.len()) as u8
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum PrecipitationUnit {
+    #[default]
+    Millimetres,
+    Inches,
+}
+
+impl PrecipitationUnit {
+    pub const ALL: [Self; 2] = [Self::Millimetres, Self::Inches];
+
+    fn api_name(self) -> &'static str {
+        match self {
+            Self::Millimetres => "mm",
+            Self::Inches => "inch",
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Millimetres => "mm",
+            Self::Inches => "in",
+        }
+    }
+
+    pub fn convert_mm(self, mm: f64) -> f64 {
+        match self {
+            Self::Millimetres => mm,
+            Self::Inches => mm / 25.4,
+        }
+    }
+
+    pub fn to_mm(self, precipitation: f64) -> f64 {
+        match self {
+            Self::Millimetres => precipitation,
+            Self::Inches => precipitation * 25.4,
+        }
+    }
+}
+
+/// Unit preference, for each quantity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Units {
+    pub temperature: TemperatureUnit,
+    pub wind_speed: WindSpeedUnit,
+    pub precipitation: PrecipitationUnit,
+}
+
+impl Units {
+    pub const METRIC: Self = Units {
+        temperature: TemperatureUnit::Celsius,
+        wind_speed: WindSpeedUnit::KilometresPerHour,
+        precipitation: PrecipitationUnit::Millimetres,
+    };
+    pub const IMPERIAL: Self = Units {
+        temperature: TemperatureUnit::Fahrenheit,
+        wind_speed: WindSpeedUnit::MilesPerHour,
+        precipitation: PrecipitationUnit::Inches,
+    };
+
+    /// Load the preference from the browser storage, or default to metric units.
+    pub fn load() -> Self {
+        storage::get(STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn store(&self) {
+        storage::set(STORAGE_KEY, self);
+    }
+
+    /// Query parameters asking open-meteo.com for these units.
+    pub fn api_parameters(&self) -> String {
+        format!(
+            "temperature_unit={}&wind_speed_unit={}&precipitation_unit={}",
+            self.temperature.api_name(),
+            self.wind_speed.api_name(),
+            self.precipitation.api_name()
+        )
+    }
+
+    /// Short form for shared links, like "celsius,kmh,mm".
+    pub fn link_value(&self) -> String {
+        format!(
+            "{},{},{}",
+            self.temperature.api_name(),
+            self.wind_speed.link_name(),
+            self.precipitation.api_name()
+        )
+    }
+
+    /// Parse the short form of [`Units::link_value`].
+    pub fn parse_link_value(value: &str) -> Option<Self> {
+        let mut names = value.split(',');
+        let units = Units {
+            temperature: named(
+                &TemperatureUnit::ALL,
+                TemperatureUnit::api_name,
+                names.next(),
+            )?,
+            wind_speed: named(&WindSpeedUnit::ALL, WindSpeedUnit::link_name, names.next())?,
+            precipitation: named(
+                &PrecipitationUnit::ALL,
+                PrecipitationUnit::api_name,
+                names.next(),
+            )?,
+        };
+        names.next().is_none().then_some(units)
+    }
+}
+
+/// The option called `name`, if any.
+fn named<T: Copy>(options: &[T], name_of: fn(T) -> &'static str, name: Option<&str>) -> Option<T> {
+    options
+        .iter()
+        .copied()
+        .find(|&option| Some(name_of(option)) == name)
+}
+
+/// Select `options[index]`, if the index is valid.
+fn select<T: Copy>(options: &[T], index: String) -> Option<T> {
+    index
+        .parse::<usize>()
+        .ok()
+        .and_then(|i| options.get(i).copied())
+}
+
+#[component]
+pub fn UnitsSelector(units: ReadSignal<Units>, set_units: WriteSignal<Units>) -> impl IntoView {
+    // Only units chosen here are remembered, not those of a shared link
+    let choose = move |chosen: Units| {
+        chosen.store();
+        set_units.set(chosen);
+    };
+
+    view! {
+        <fieldset>
+            <legend>"Units 📏"</legend>
+            <button type="button" on:click=move |_| choose(Units::METRIC)>"Metric"</button>
+            <button type="button" on:click=move |_| choose(Units::IMPERIAL)>"Imperial"</button>
+            <select
+                aria-label="Temperature unit"
+                on:change=move |ev| {
+                    if let Some(unit) = select(&TemperatureUnit::ALL, event_target_value(&ev)) {
+                        choose(Units { temperature: unit, ..units.get_untracked() });
+                    }
+                }
+            >
+                {TemperatureUnit::ALL.into_iter().enumerate().map(|(i, unit)| view! {
+                    <option value=i prop:selected=move || units.get().temperature == unit>{unit.symbol()}</option>
+                }).collect_view()}
+            </select>
+            <select
+                aria-label="Wind speed unit"
+                on:change=move |ev| {
+                    if let Some(unit) = select(&WindSpeedUnit::ALL, event_target_value(&ev)) {
+                        choose(Units { wind_speed: unit, ..units.get_untracked() });
+                    }
+                }
+            >
+                {WindSpeedUnit::ALL.into_iter().enumerate().map(|(i, unit)| view! {
+                    <option value=i prop:selected=move || units.get().wind_speed == unit>{unit.symbol()}</option>
+                }).collect_view()}
+            </select>
+            <select
+                aria-label="Precipitation unit"
+                on:change=move |ev| {
+                    if let Some(unit) = select(&PrecipitationUnit::ALL, event_target_value(&ev)) {
+                        choose(Units { precipitation: unit, ..units.get_untracked() });
+                    }
+                }
+            >
+                {PrecipitationUnit::ALL.into_iter().enumerate().map(|(i, unit)| view! {
+                    <option value=i prop:selected=move || units.get().precipitation == unit>{unit.symbol()}</option>
+                }).collect_view()}
+            </select>
+        </fieldset>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temperature_conversion() {
+        let unit = TemperatureUnit::Fahrenheit;
+        assert_eq!(unit.convert_celsius(0.0), 32.0);
+        assert_eq!(unit.convert_celsius(-40.0), -40.0);
+    }
+
+    #[test]
+    fn beaufort_scale() {
+        assert_eq!(beaufort(0.0), 0);
+        assert_eq!(beaufort(5.0), 3);
+        assert_eq!(beaufort(10.8), 6);
+        assert_eq!(beaufort(40.0), 12);
+    }
+
+    #[test]
+    fn api_parameters() {
+        assert_eq!(
+            Units::IMPERIAL.api_parameters(),
+            "temperature_unit=fahrenheit&wind_speed_unit=mph&precipitation_unit=inch"
+        );
+    }
+}
@@ -0,0 +1,428 @@
+//! Weather conditions, as WMO 4677 codes used by open-meteo.com.
+
+use leptos::*;
+
+use crate::theme::{use_palette, Palette};
+
+/// Weather condition reported by open-meteo.com.
+///
+/// Intensities are ordered from light to heavy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(from = "u8", into = "u8")]
+pub enum WeatherCode {
+    ClearSky,
+    MainlyClear,
+    PartlyCloudy,
+    Overcast,
+    Fog,
+    DepositingRimeFog,
+    Drizzle(Intensity),
+    FreezingDrizzle(Intensity),
+    Rain(Intensity),
+    FreezingRain(Intensity),
+    Snowfall(Intensity),
+    SnowGrains,
+    RainShowers(Intensity),
+    SnowShowers(Intensity),
+    Thunderstorm,
+    ThunderstormWithHail(Intensity),
+    /// A code not defined by WMO 4677 for present weather.
+    Unknown(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Intensity {
+    Light,
+    Moderate,
+    Heavy,
+}
+
+impl Intensity {
+    fn adjective(self) -> &'static str {
+        match self {
+            Intensity::Light => "Light",
+            Intensity::Moderate => "Moderate",
+            Intensity::Heavy => "Heavy",
+        }
+    }
+}
+
+impl From<u8> for WeatherCode {
+    fn from(code: u8) -> Self {
+        use Intensity::*;
+        use WeatherCode::*;
+        match code {
+            0 => ClearSky,
+            1 => MainlyClear,
+            2 => PartlyCloudy,
+            3 => Overcast,
+            45 => Fog,
+            48 => DepositingRimeFog,
+            51 => Drizzle(Light),
+            53 => Drizzle(Moderate),
+            55 => Drizzle(Heavy),
+            56 => FreezingDrizzle(Light),
+            57 => FreezingDrizzle(Heavy),
+            61 => Rain(Light),
+            63 => Rain(Moderate),
+            65 => Rain(Heavy),
+            66 => FreezingRain(Light),
+            67 => FreezingRain(Heavy),
+            71 => Snowfall(Light),
+            73 => Snowfall(Moderate),
+            75 => Snowfall(Heavy),
+            77 => SnowGrains,
+            80 => RainShowers(Light),
+            81 => RainShowers(Moderate),
+            82 => RainShowers(Heavy),
+            85 => SnowShowers(Light),
+            86 => SnowShowers(Heavy),
+            95 => Thunderstorm,
+            96 => ThunderstormWithHail(Light),
+            99 => ThunderstormWithHail(Heavy),
+            code => Unknown(code),
+        }
+    }
+}
+
+impl From<WeatherCode> for u8 {
+    fn from(code: WeatherCode) -> u8 {
+        use Intensity::*;
+        use WeatherCode::*;
+        match code {
+            ClearSky => 0,
+            MainlyClear => 1,
+            PartlyCloudy => 2,
+            Overcast => 3,
+            Fog => 45,
+            DepositingRimeFog => 48,
+            Drizzle(Light) => 51,
+            Drizzle(Moderate) => 53,
+            Drizzle(Heavy) => 55,
+            FreezingDrizzle(Light | Moderate) => 56,
+            FreezingDrizzle(Heavy) => 57,
+            Rain(Light) => 61,
+            Rain(Moderate) => 63,
+            Rain(Heavy) => 65,
+            FreezingRain(Light | Moderate) => 66,
+            FreezingRain(Heavy) => 67,
+            Snowfall(Light) => 71,
+            Snowfall(Moderate) => 73,
+            Snowfall(Heavy) => 75,
+            SnowGrains => 77,
+            RainShowers(Light) => 80,
+            RainShowers(Moderate) => 81,
+            RainShowers(Heavy) => 82,
+            SnowShowers(Light | Moderate) => 85,
+            SnowShowers(Heavy) => 86,
+            Thunderstorm => 95,
+            ThunderstormWithHail(Light | Moderate) => 96,
+            ThunderstormWithHail(Heavy) => 99,
+            Unknown(code) => code,
+        }
+    }
+}
+
+impl WeatherCode {
+    pub fn description(self) -> String {
+        use WeatherCode::*;
+        match self {
+            ClearSky => "Clear sky".to_string(),
+            MainlyClear => "Mainly clear".to_string(),
+            PartlyCloudy => "Partly cloudy".to_string(),
+            Overcast => "Overcast".to_string(),
+            Fog => "Fog".to_string(),
+            DepositingRimeFog => "Depositing rime fog".to_string(),
+            Drizzle(i) => format!("{} drizzle", i.adjective()),
+            FreezingDrizzle(i) => format!("{} freezing drizzle", i.adjective()),
+            Rain(i) => format!("{} rain", i.adjective()),
+            FreezingRain(i) => format!("{} freezing rain", i.adjective()),
+            Snowfall(i) => format!("{} snowfall", i.adjective()),
+            SnowGrains => "Snow grains".to_string(),
+            RainShowers(i) => format!("{} rain showers", i.adjective()),
+            SnowShowers(i) => format!("{} snow showers", i.adjective()),
+            Thunderstorm => "Thunderstorm".to_string(),
+            ThunderstormWithHail(i) => {
+                format!("Thunderstorm with {} hail", i.adjective().to_lowercase())
+            }
+            Unknown(code) => format!("Unknown weather (code {code})"),
+        }
+    }
+
+    pub fn emoji(self, is_day: bool) -> &'static str {
+        use WeatherCode::*;
+        match (self, is_day) {
+            (ClearSky, true) => "☀️",
+            (MainlyClear, true) => "🌤",
+            (PartlyCloudy, true) => "⛅",
+            (ClearSky | MainlyClear | PartlyCloudy, false) => "🌙",
+            (Overcast, _) => "☁️",
+            (Fog | DepositingRimeFog, _) => "🌫",
+            (Drizzle(_) | RainShowers(_), true) => "🌦",
+            (Drizzle(_) | RainShowers(_), false) => "🌧",
+            (Rain(_) | FreezingDrizzle(_) | FreezingRain(_), _) => "🌧",
+            (Snowfall(_) | SnowGrains | SnowShowers(_), _) => "🌨",
+            (Thunderstorm | ThunderstormWithHail(_), _) => "⛈",
+            (Unknown(_), _) => "❔",
+        }
+    }
+
+    /// How much of the sun, or moon, shows through the clouds.
+    fn sky(self) -> Sky {
+        use WeatherCode::*;
+        match self {
+            ClearSky => Sky::Clear,
+            MainlyClear | PartlyCloudy | RainShowers(_) | SnowShowers(_) => Sky::Broken,
+            Fog | DepositingRimeFog => Sky::Fog,
+            _ => Sky::Overcast,
+        }
+    }
+
+    /// What falls from the clouds, and how much of it.
+    fn falling(self) -> Option<(Falling, Intensity)> {
+        use Intensity::*;
+        use WeatherCode::*;
+        match self {
+            Drizzle(i) | Rain(i) | RainShowers(i) => Some((Falling::Rain, i)),
+            FreezingDrizzle(i) | FreezingRain(i) => Some((Falling::Sleet, i)),
+            Snowfall(i) | SnowShowers(i) => Some((Falling::Snow, i)),
+            SnowGrains => Some((Falling::Snow, Light)),
+            Thunderstorm => Some((Falling::Lightning, Moderate)),
+            ThunderstormWithHail(i) => Some((Falling::Lightning, i)),
+            _ => None,
+        }
+    }
+}
+
+enum Sky {
+    Clear,
+    Broken,
+    Overcast,
+    Fog,
+}
+
+enum Falling {
+    Rain,
+    Sleet,
+    Snow,
+    Lightning,
+}
+
+/// Colour of a part of a weather symbol, from the palette of the theme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconColor {
+    Sun,
+    Moon,
+    Cloud,
+    Rain,
+    Snow,
+}
+
+impl IconColor {
+    pub fn of(self, palette: &Palette) -> &'static str {
+        match self {
+            IconColor::Sun => palette.sun,
+            IconColor::Moon => palette.moon,
+            IconColor::Cloud => palette.cloud,
+            IconColor::Rain => palette.rain,
+            IconColor::Snow => palette.snow,
+        }
+    }
+}
+
+/// Part of a weather symbol: an SVG path in the 10 x 10 box of the symbol, drawn
+/// the same way as SVG elements and on a canvas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IconShape {
+    pub d: String,
+    pub fill: Option<IconColor>,
+    /// Colour and width of the outline, with round caps.
+    pub stroke: Option<(IconColor, f64)>,
+}
+
+impl IconShape {
+    fn filled(d: impl Into<String>, fill: IconColor) -> Self {
+        IconShape {
+            d: d.into(),
+            fill: Some(fill),
+            stroke: None,
+        }
+    }
+
+    fn stroked(d: impl Into<String>, stroke: IconColor, width: f64) -> Self {
+        IconShape {
+            d: d.into(),
+            fill: None,
+            stroke: Some((stroke, width)),
+        }
+    }
+}
+
+/// Path of a circle.
+fn circle(cx: f64, cy: f64, r: f64) -> String {
+    format!(
+        "M{} {cy}a{r} {r} 0 1 0 {} 0a{r} {r} 0 1 0 {} 0z",
+        cx - r,
+        2.0 * r,
+        -2.0 * r
+    )
+}
+
+impl WeatherCode {
+    /// Parts of the weather symbol, from back to front.
+    pub fn icon_shapes(self, is_day: bool) -> Vec<IconShape> {
+        let sky = self.sky();
+        let mut shapes = Vec::new();
+        match (is_day, &sky) {
+            (_, Sky::Overcast | Sky::Fog) => (),
+            (true, Sky::Clear) => {
+                shapes.push(IconShape::filled(circle(5.0, 4.0, 3.0), IconColor::Sun))
+            }
+            (true, Sky::Broken) => {
+                shapes.push(IconShape::filled(circle(3.5, 3.0, 2.5), IconColor::Sun))
+            }
+            (false, Sky::Clear) => shapes.push(IconShape::filled(
+                "M5 1a3 3 0 1 0 3 4a2.4 2.4 0 1 1-3-4z",
+                IconColor::Moon,
+            )),
+            (false, Sky::Broken) => shapes.push(IconShape::filled(
+                "M3.5 0.5a2.5 2.5 0 1 0 2.5 3.3a2 2 0 1 1-2.5-3.3z",
+                IconColor::Moon,
+            )),
+        }
+        match sky {
+            Sky::Clear => (),
+            Sky::Fog => shapes.push(IconShape::stroked(
+                "M1 3h8M2 5h6M1 7h8",
+                IconColor::Cloud,
+                0.8,
+            )),
+            Sky::Broken | Sky::Overcast => shapes.push(IconShape::filled(
+                "M2.5 7.5a1.8 1.8 0 0 1 0.3-3.6a2.6 2.6 0 0 1 5-0.5a2 2 0 0 1 0.2 4.1z",
+                IconColor::Cloud,
+            )),
+        }
+        if let Some((falling, intensity)) = self.falling() {
+            let count = match intensity {
+                Intensity::Light => 1,
+                Intensity::Moderate => 2,
+                Intensity::Heavy => 3,
+            };
+            shapes.extend((0..count).map(|i| {
+                let x = 3.0 + 2.0 * i as f64;
+                match falling {
+                    Falling::Rain => IconShape::stroked(
+                        format!("M{x} 8.3L{} 9.8", x - 0.5),
+                        IconColor::Rain,
+                        0.6,
+                    ),
+                    Falling::Sleet => IconShape {
+                        stroke: Some((IconColor::Rain, 0.3)),
+                        ..IconShape::filled(circle(x, 9.0, 0.6), IconColor::Snow)
+                    },
+                    Falling::Snow => IconShape {
+                        stroke: Some((IconColor::Cloud, 0.3)),
+                        ..IconShape::filled(circle(x, 9.0, 0.7), IconColor::Snow)
+                    },
+                    Falling::Lightning => IconShape::stroked(
+                        format!("M{} 7.8l-0.8 1.2h1l-0.8 1.2", x + 0.3),
+                        IconColor::Sun,
+                        0.5,
+                    ),
+                }
+            }));
+        }
+        shapes
+    }
+}
+
+/// Weather symbol drawn in a 10 x 10 box, to be placed inside an `<svg>`.
+#[component]
+pub fn WeatherIcon(code: WeatherCode, is_day: bool, x: f64, y: f64, size: f64) -> impl IntoView {
+    let palette = use_palette();
+    let shapes = code
+        .icon_shapes(is_day)
+        .into_iter()
+        .map(|shape| {
+            let fill = move || shape.fill.map_or("none", |color| color.of(&palette.get()));
+            let stroke = shape.stroke;
+            let stroke_color = move || stroke.map(|(color, _)| color.of(&palette.get()));
+            view! {
+                <path
+                    d=shape.d
+                    fill=fill
+                    stroke=stroke_color
+                    stroke-width=stroke.map(|(_, width)| width)
+                    stroke-linecap="round"
+                    stroke-linejoin="round"
+                />
+            }
+        })
+        .collect_view();
+
+    view! {
+        <g transform={format!("translate({x} {y}) scale({})", size / 10.0)}>
+            <title>{format!("{} {}", code.emoji(is_day), code.description())}</title>
+            {shapes}
+        </g>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        let codes = [
+            0, 1, 2, 3, 45, 48, 51, 53, 55, 56, 57, 61, 63, 65, 66, 67, 71, 73, 75, 77, 80, 81, 82,
+            85, 86, 95, 96, 99,
+        ];
+        for code in codes {
+            let weather = WeatherCode::from(code);
+            assert!(!matches!(weather, WeatherCode::Unknown(_)), "{code}");
+            assert_eq!(u8::from(weather), code);
+        }
+        assert_eq!(WeatherCode::from(42), WeatherCode::Unknown(42));
+    }
+
+    #[test]
+    fn deserialization() -> Result<(), serde_json::Error> {
+        let codes: Vec<WeatherCode> = serde_json::from_str("[0, 63, 99]")?;
+        assert_eq!(
+            codes,
+            [
+                WeatherCode::ClearSky,
+                WeatherCode::Rain(Intensity::Moderate),
+                WeatherCode::ThunderstormWithHail(Intensity::Heavy)
+            ]
+        );
+        assert_eq!(codes[2].description(), "Thunderstorm with heavy hail");
+        Ok(())
+    }
+
+    #[test]
+    fn icon_shapes() {
+        assert_eq!(circle(5.0, 4.0, 3.0), "M2 4a3 3 0 1 0 6 0a3 3 0 1 0 -6 0z");
+        let colors = |code: WeatherCode, is_day: bool| -> Vec<_> {
+            code.icon_shapes(is_day)
+                .into_iter()
+                .map(|shape| (shape.fill, shape.stroke.map(|(color, _)| color)))
+                .collect()
+        };
+        assert_eq!(
+            colors(WeatherCode::ClearSky, false),
+            [(Some(IconColor::Moon), None)]
+        );
+        // Snow is outlined, so that it shows on a white background
+        assert_eq!(
+            colors(WeatherCode::SnowShowers(Intensity::Moderate), true),
+            [
+                (Some(IconColor::Sun), None),
+                (Some(IconColor::Cloud), None),
+                (Some(IconColor::Snow), Some(IconColor::Cloud)),
+                (Some(IconColor::Snow), Some(IconColor::Cloud)),
+            ]
+        );
+    }
+}
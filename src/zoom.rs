@@ -0,0 +1,137 @@
+//! Visible part of the time axis, shared by all charts.
+//!
+//! Times are counted in hours since the first hour of the forecast, so that every
+//! chart, hourly or daily, can derive its viewBox from the same range.
+
+/// Shortest range that can be zoomed to, in hours.
+pub const MIN_HOURS: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeRange {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl TimeRange {
+    pub fn new(start: f64, end: f64) -> Self {
+        TimeRange { start, end }
+    }
+
+    /// The whole forecast, for `hours` hours.
+    pub fn all(hours: usize) -> Self {
+        TimeRange::new(0.0, hours as f64)
+    }
+
+    pub fn len(&self) -> f64 {
+        self.end - self.start
+    }
+
+    /// Hour at a fraction of the range, from 0 (start) to 1 (end).
+    pub fn at(&self, fraction: f64) -> f64 {
+        self.start + fraction * self.len()
+    }
+
+    /// Hours overlapping the range, as indices.
+    pub fn indices(&self, hours: usize) -> std::ops::Range<usize> {
+        let start = self.start.floor().max(0.0) as usize;
+        let end = (self.end.ceil().max(0.0) as usize).min(hours);
+        start.min(end)..end
+    }
+
+    /// Same length, moved inside `bounds`, and no shorter than `MIN_HOURS`.
+    pub fn clamp(self, bounds: TimeRange) -> Self {
+        let len = self.len().clamp(MIN_HOURS.min(bounds.len()), bounds.len());
+        let start = self.start.clamp(bounds.start, bounds.end - len);
+        TimeRange::new(start, start + len)
+    }
+
+    /// Zoom by `factor` (below 1 to zoom in), keeping `center` at the same place.
+    pub fn zoom(self, factor: f64, center: f64, bounds: TimeRange) -> Self {
+        let len = (self.len() * factor).clamp(MIN_HOURS.min(bounds.len()), bounds.len());
+        let start = center - (center - self.start) * len / self.len();
+        TimeRange::new(start, start + len).clamp(bounds)
+    }
+
+    /// Range keeping each anchored hour under its pointer, given as a fraction of
+    /// the plot width. One anchor pans, two anchors pinch-zoom.
+    pub fn anchored(self, anchors: &[(f64, f64)], bounds: TimeRange) -> Self {
+        let range = match anchors {
+            [(hour, fraction)] => {
+                let start = hour - fraction * self.len();
+                TimeRange::new(start, start + self.len())
+            }
+            [(hour_a, fraction_a), (hour_b, fraction_b), ..]
+                if (fraction_b - fraction_a).abs() > f64::EPSILON =>
+            {
+                let len = (hour_b - hour_a) / (fraction_b - fraction_a);
+                if len <= 0.0 {
+                    return self;
+                }
+                let start = hour_a - fraction_a * len;
+                TimeRange::new(start, start + len)
+            }
+            _ => return self,
+        };
+        range.clamp(bounds)
+    }
+}
+
+/// Hours between two time labels, so that labels do not overlap.
+pub fn tick_step(visible_hours: f64) -> usize {
+    const STEPS: [usize; 5] = [1, 3, 6, 12, 24];
+    // About one label every 40 user units of a 480 units wide plot
+    const MAX_LABELS: f64 = 12.0;
+    STEPS
+        .into_iter()
+        .find(|&step| visible_hours / step as f64 <= MAX_LABELS)
+        .unwrap_or(48)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zoom_keeps_center() {
+        let bounds = TimeRange::all(48);
+        let range = bounds.zoom(0.5, 12.0, bounds);
+        assert_eq!(range, TimeRange::new(6.0, 30.0));
+        // Never shorter than the minimum, never outside the forecast
+        assert_eq!(range.zoom(0.01, 6.0, bounds).len(), MIN_HOURS);
+        assert_eq!(range.zoom(10.0, 6.0, bounds), bounds);
+    }
+
+    #[test]
+    fn pan_and_pinch() {
+        let bounds = TimeRange::all(48);
+        let range = TimeRange::new(0.0, 24.0);
+        // Hour 12 was grabbed at the middle, and dragged to the left quarter
+        assert_eq!(
+            range.anchored(&[(12.0, 0.25)], bounds),
+            TimeRange::new(6.0, 30.0)
+        );
+        // Panning stops at the end of the forecast
+        assert_eq!(
+            range.anchored(&[(12.0, -1.0)], bounds),
+            TimeRange::new(24.0, 48.0)
+        );
+        // Fingers on hours 6 and 18 spread to the edges
+        assert_eq!(
+            range.anchored(&[(6.0, 0.0), (18.0, 1.0)], bounds),
+            TimeRange::new(6.0, 18.0)
+        );
+    }
+
+    #[test]
+    fn visible_indices() {
+        assert_eq!(TimeRange::new(2.5, 5.2).indices(48), 2..6);
+        assert_eq!(TimeRange::new(40.0, 50.0).indices(48), 40..48);
+    }
+
+    #[test]
+    fn tick_density() {
+        assert_eq!(tick_step(6.0), 1);
+        assert_eq!(tick_step(48.0), 6);
+        assert_eq!(tick_step(384.0), 48);
+    }
+}
@@ -0,0 +1,83 @@
+// Service worker: makes the app usable offline.
+//
+// Only the app is cached here. Forecasts are cached by the app itself (see
+// `src/cache.rs`), which knows when they were downloaded and tells when they
+// are outdated.
+//
+// Every path is relative to this file, so that it works both under `trunk serve`
+// and under the `docs/` folder published to GitHub Pages.
+
+const APP_CACHE = "weather-app-v2";
+const APP_SHELL = ["./", "./index.html", "./manifest.webmanifest", "./icon.svg"];
+
+self.addEventListener("install", (event) => {
+	event.waitUntil(precache());
+	self.skipWaiting();
+});
+
+// Cache the app shell and the wasm/js bundle, so that the first offline launch works
+async function precache() {
+	const cache = await caches.open(APP_CACHE);
+	await cache.addAll(APP_SHELL);
+	// The bundle has hashed file names, only known from the preload links Trunk
+	// adds to the built index.html
+	const page = await (await cache.match("./index.html")).text();
+	const bundle = [...page.matchAll(/<link rel="(?:modulepreload|preload)" href="([^"]+)"/g)]
+		.map((link) => new URL(link[1], self.location.href).href);
+	await cache.addAll(bundle);
+}
+
+self.addEventListener("activate", (event) => {
+	// Drop caches of previous versions
+	event.waitUntil(
+		caches.keys().then((keys) => Promise.all(
+			keys
+				.filter((key) => key !== APP_CACHE)
+				.map((key) => caches.delete(key))
+		)).then(() => self.clients.claim())
+	);
+});
+
+self.addEventListener("fetch", (event) => {
+	const request = event.request;
+	if (request.method !== "GET") {
+		return;
+	}
+	const url = new URL(request.url);
+	if (request.mode === "navigate") {
+		event.respondWith(networkFirst(request, APP_CACHE));
+	} else if (url.origin === self.location.origin) {
+		// The wasm/js bundle has hashed file names: a cached copy is never outdated
+		event.respondWith(cacheFirst(request, APP_CACHE));
+	}
+});
+
+async function networkFirst(request, cacheName) {
+	const cache = await caches.open(cacheName);
+	try {
+		const response = await fetch(request);
+		if (response.ok) {
+			await cache.put(request, response.clone());
+		}
+		return response;
+	} catch (error) {
+		const cached = await cache.match(request, { ignoreSearch: true });
+		if (cached) {
+			return cached;
+		}
+		throw error;
+	}
+}
+
+async function cacheFirst(request, cacheName) {
+	const cache = await caches.open(cacheName);
+	const cached = await cache.match(request);
+	if (cached) {
+		return cached;
+	}
+	const response = await fetch(request);
+	if (response.ok) {
+		await cache.put(request, response.clone());
+	}
+	return response;
+}